@@ -0,0 +1,161 @@
+use crate::*;
+
+/// Follows a game played elsewhere, of which only the face-up cards are known.
+///
+/// Every action that turns a card face up waits until that card is given to
+/// `reveal`, before it is applied to the state.
+pub struct Assistant {
+	view: PlayerView,
+	/// An action that turned cards face up, and the places of those cards in
+	/// the deck, in the order they were turned over.
+	pending: Option<(Action, Vec<usize>)>,
+	agent: Box<dyn Agent>,
+}
+
+impl Assistant {
+	/// Starts a game where the face-up cards of the columns are `open`, from left to right.
+	pub fn new(rules: Rules, open: [Card; 7]) -> Result<Self, RevealError> {
+		let mut known = [None; 52];
+		for (column, &card) in open.iter().enumerate() {
+			if known.contains(&Some(card)) {
+				return Err(RevealError::AlreadySeen);
+			}
+			known[ClosedState::position(column, column)] = Some(card);
+		}
+		let mut rest = Card::all().filter(|card| !open.contains(card));
+		let mut deck = Deck::new();
+		for (card, known) in deck.cards.iter_mut().zip(&known) {
+			*card = known.unwrap_or_else(|| rest.next().unwrap());
+		}
+		let view = PlayerView { known, state: GameState::new(&deck), rules };
+		let mut agent: Box<dyn Agent> = Box::new(MonteCarloAgent::new());
+		agent.new_game(&view);
+		Ok(Self { view, pending: None, agent })
+	}
+
+	/// Sets the agent that gives the hints.
+	pub fn agent(mut self, mut agent: Box<dyn Agent>) -> Self {
+		agent.new_game(&self.view);
+		self.agent = agent;
+		self
+	}
+
+	/// Everything known about the game so far.
+	pub fn view(&self) -> &PlayerView {
+		&self.view
+	}
+
+	/// The places in the deck of the cards that have to be revealed next, in order.
+	pub fn waiting_for(&self) -> Vec<usize> {
+		match &self.pending {
+			Some((_, places)) => places.iter().copied().filter(|&i| self.view.known[i].is_none()).collect(),
+			None => Vec::new(),
+		}
+	}
+
+	/// Applies an action, and then any safe moves if the rules say so.
+	///
+	/// This stops early when a card is turned face up. The rest happens once
+	/// all such cards have been revealed.
+	pub fn action(&mut self, action: Action) -> Result<(), AssistError> {
+		if self.pending.is_some() {
+			return Err(AssistError::RevealPending);
+		}
+		self.step(action)?;
+		self.settle();
+		Ok(())
+	}
+
+	/// Gives the next card that was turned face up.
+	pub fn reveal(&mut self, card: Card) -> Result<(), RevealError> {
+		let place = *self.waiting_for().first().ok_or(RevealError::NothingToReveal)?;
+		if self.view.known.contains(&Some(card)) {
+			return Err(RevealError::AlreadySeen);
+		}
+		self.view.known[place] = Some(card);
+		if self.waiting_for().is_empty() {
+			let (action, _) = self.pending.take().unwrap();
+			self.step(action).unwrap();
+			self.settle();
+		}
+		Ok(())
+	}
+
+	/// Suggests a move, using the agent.
+	///
+	/// Returns `None` while waiting for a card to be revealed.
+	pub fn hint(&mut self) -> Option<Action> {
+		if self.pending.is_some() {
+			return None;
+		}
+		self.agent.choose(&self.view)
+	}
+
+	/// Applies a single action, or makes it pending if it turns up unknown cards.
+	fn step(&mut self, action: Action) -> Result<(), ActionError> {
+		let deck = self.view.deck();
+		let mut game = self.view.game(&deck);
+		game.apply(action)?;
+		let state = &game.state;
+		let mut places = Vec::new();
+		for column in 0..7 {
+			places.extend((state.closed.n_closed(column)..=column).map(|level| ClosedState::position(column, level)));
+		}
+		places.extend((0..24).rev().filter(|&i| state.stock.is_turned(i)));
+		places.retain(|&i| self.view.known[i].is_none());
+		if places.is_empty() {
+			self.view.state = game.state;
+		} else {
+			self.pending = Some((action, places));
+		}
+		Ok(())
+	}
+
+	fn settle(&mut self) {
+		while self.pending.is_none() && self.view.rules.auto_foundation {
+			let deck = self.view.deck();
+			match self.view.game(&deck).safe_move() {
+				Some(action) => self.step(action).unwrap(),
+				None => break,
+			}
+		}
+	}
+}
+
+#[test]
+fn test() {
+	// Play along with a real game, only passing on the cards it shows.
+	for &rules in &[Rules::draw_three(), Rules { auto_foundation: true, ..Rules::draw_one() }] {
+		let deck = Deck::from_seed(0);
+		let mut game = Game::with_rules(&deck, rules);
+		let open = [0, 1, 2, 3, 4, 5, 6].map(|c| deck.cards[ClosedState::position(c, c)]);
+		let mut assistant = Assistant::new(rules, open).unwrap();
+		assert_eq!(assistant.waiting_for(), []);
+		assert_eq!(assistant.reveal(open[0]), Err(RevealError::NothingToReveal));
+		let actions = Solver::new().solve(&game).outcome.unwrap_solved();
+		for action in actions {
+			game.action(action).unwrap();
+			assistant.action(action).unwrap();
+			while let Some(&i) = assistant.waiting_for().first() {
+				assert_eq!(assistant.action(action), Err(AssistError::RevealPending));
+				assert_eq!(assistant.hint(), None);
+				assert_eq!(assistant.reveal(open[0]), Err(RevealError::AlreadySeen));
+				assistant.reveal(deck.cards[i]).unwrap();
+			}
+			assert_eq!(assistant.view().state, game.state);
+		}
+		assert!(assistant.view().state.foundation.is_complete());
+	}
+
+	let open = [King.of(Spades); 7];
+	assert!(Assistant::new(Rules::new(), open).is_err());
+
+	let deck = Deck::new();
+	let open = [0, 1, 2, 3, 4, 5, 6].map(|c| deck.cards[ClosedState::position(c, c)]);
+	let agent = MonteCarloAgent::new().solver(Solver::new().max_nodes(100)).samples(2);
+	let mut assistant = Assistant::new(Rules::new(), open).unwrap().agent(Box::new(agent));
+	let wrong = Action::StackToStack { from: 1, to: 4, n: 1 };
+	assert_eq!(assistant.action(wrong), Err(AssistError::Action(ActionError::WrongRank)));
+	let action = assistant.hint().unwrap();
+	assert!(assistant.action(action).is_ok());
+}
@@ -46,6 +46,33 @@ impl Card {
 	}
 }
 
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct ParseCardError;
+
+impl std::error::Error for ParseCardError {}
+
+impl std::str::FromStr for Card {
+	type Err = ParseCardError;
+
+	/// Parses a card like `♥Q`, `hq` or `QH`. Tens can be written as `T` or `10`.
+	fn from_str(s: &str) -> Result<Self, ParseCardError> {
+		let s = s.to_uppercase().replace("10", "T");
+		let suit = |c| Suit::all().find(|&suit| {
+			c == suit.to_string().chars().next().unwrap() || c == format!("{:?}", suit).chars().next().unwrap()
+		});
+		let rank = |c| Rank::all().find(|&rank| c == rank.to_string().chars().next().unwrap());
+		let mut chars = s.chars();
+		let (a, b) = match (chars.next(), chars.next(), chars.next()) {
+			(Some(a), Some(b), None) => (a, b),
+			_ => return Err(ParseCardError),
+		};
+		match (suit(a), rank(b), rank(a), suit(b)) {
+			(Some(suit), Some(rank), _, _) | (_, _, Some(rank), Some(suit)) => Ok(rank.of(suit)),
+			_ => Err(ParseCardError),
+		}
+	}
+}
+
 #[test]
 fn test() {
 	assert_eq!(Card::all().count(), 52);
@@ -62,4 +89,14 @@ fn test() {
 	}
 	assert_eq!(Card::option_to_bits(None, 0), 0);
 	assert_eq!(Card::option_from_bits(0), (None, 0));
+	for card in Card::all() {
+		assert_eq!(card.to_string().parse(), Ok(card));
+	}
+	assert_eq!("hq".parse(), Ok(Queen.of(Hearts)));
+	assert_eq!("QH".parse(), Ok(Queen.of(Hearts)));
+	assert_eq!("10d".parse(), Ok(Ten.of(Diamonds)));
+	assert_eq!("♣t".parse(), Ok(Ten.of(Clubs)));
+	for &s in &["", "h", "hh", "qq", "1h", "hq2", "xq"] {
+		assert_eq!(s.parse::<Card>(), Err(ParseCardError));
+	}
 }
@@ -18,16 +18,34 @@ impl ClosedState {
 		}
 	}
 
-	pub fn open(&mut self, column: usize, deck: &Deck) -> Result<Card, ()> {
+	pub fn n_closed_total(&self) -> usize {
+		(0..7).map(|column| self.n_closed(column)).sum()
+	}
+
+	pub fn open(&mut self, column: usize, deck: &Deck) -> Result<Card, ActionError> {
 		if let Some(n) = self.n_closed(column).checked_sub(1) {
 			self.state -= 1 << shift(column);
-			let index = 51 - [0, 6, 11, 15, 18, 20][n] - column;
-			Ok(deck.cards[index])
+			Ok(deck.cards[Self::position(column, n)])
 		} else {
-			Err(())
+			Err(ActionError::EmptySource)
 		}
 	}
 
+	/// The index in the deck of the card dealt to `column` at `level`.
+	///
+	/// Level 0 is the bottom card. Level `column` is the card that is dealt
+	/// face up, and the levels below it are dealt face down.
+	pub fn position(column: usize, level: usize) -> usize {
+		assert!(level <= column && column < 7);
+		51 - [0, 6, 11, 15, 18, 20, 21][level] - column
+	}
+
+	/// Turns the last opened card of a column face down again.
+	pub(crate) fn close(&mut self, column: usize) {
+		assert!(self.n_closed(column) < column);
+		self.state += 1 << shift(column);
+	}
+
 	pub fn to_bits(&self, two_extra_bits: u8) -> u16 {
 		assert!(two_extra_bits < 4);
 		let a = u16::from(two_extra_bits >> 1);
@@ -43,6 +61,12 @@ impl ClosedState {
 	}
 }
 
+impl Default for ClosedState {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
 fn shift(column: usize) -> u8 {
 	assert!(column < 7);
 	(18 - column * 3) as u8
@@ -66,10 +90,11 @@ fn test() {
 	for i in 0..7 {
 		assert_eq!(state.n_closed(i), i);
 	}
+	assert_eq!(state.n_closed_total(), 21);
 	assert_eq!(ClosedState::from_bits(state.to_bits(0)), (state.clone(), 0));
-	assert_eq!(state.open(0, &deck), Err(()));
+	assert_eq!(state.open(0, &deck), Err(ActionError::EmptySource));
 	assert_eq!(state.open(1, &deck), Ok(Queen.of(Diamonds)));
-	assert_eq!(state.open(1, &deck), Err(()));
+	assert_eq!(state.open(1, &deck), Err(ActionError::EmptySource));
 	assert_eq!(state.n_closed(0), 0);
 	assert_eq!(state.n_closed(1), 0);
 	assert_eq!(state.n_closed(2), 2);
@@ -84,6 +109,13 @@ fn test() {
 	assert_eq!(state.open(5, &deck), Ok(Two.of(Diamonds)));
 	assert_eq!(ClosedState::from_bits(state.to_bits(2)), (state.clone(), 2));
 	assert_eq!(state.open(5, &deck), Ok(Eight.of(Diamonds)));
-	assert_eq!(state.open(5, &deck), Err(()));
+	assert_eq!(state.open(5, &deck), Err(ActionError::EmptySource));
 	assert_eq!(state.n_closed(5), 0);
+	state.close(5);
+	assert_eq!(state.n_closed(5), 1);
+	assert_eq!(state.open(5, &deck), Ok(Eight.of(Diamonds)));
+	assert_eq!(ClosedState::position(0, 0), 51);
+	assert_eq!(ClosedState::position(1, 1), 44);
+	assert_eq!(ClosedState::position(6, 6), 24);
+	assert_eq!(deck.cards[ClosedState::position(6, 5)], King.of(Hearts));
 }
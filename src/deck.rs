@@ -1,4 +1,5 @@
 use crate::*;
+use rand::RngCore;
 
 #[derive(Clone)]
 pub struct Deck {
@@ -13,6 +14,72 @@ impl Deck {
 		}
 		Self { cards }
 	}
+
+	/// Deals deck number `seed`.
+	///
+	/// The same seed gives the same deck on every platform and in every
+	/// release: the sorted deck from `Deck::new` is shuffled by `shuffle`,
+	/// using SplitMix64 seeded with `seed` as the random number generator.
+	pub fn from_seed(seed: u64) -> Self {
+		let mut deck = Self::new();
+		deck.shuffle(&mut SplitMix64(seed));
+		deck
+	}
+
+	/// Shuffles the deck.
+	///
+	/// This is a Fisher-Yates shuffle, that swaps every position `i` from 51
+	/// down to 1 with a position in `0..=i`. Each of those positions is
+	/// `x % (i + 1)` for the first `x` from `rng.next_u64()` that doesn't
+	/// fall in the incomplete last block of `i + 1` values, to avoid bias.
+	pub fn shuffle(&mut self, rng: &mut (impl RngCore + ?Sized)) {
+		for i in (1..52).rev() {
+			let n = i as u64 + 1;
+			let reject = (u64::MAX % n + 1) % n;
+			let x = loop {
+				let x = rng.next_u64();
+				if x <= u64::MAX - reject {
+					break x;
+				}
+			};
+			self.cards.swap(i, (x % n) as usize);
+		}
+	}
+}
+
+/// SplitMix64, as described by Steele, Lea, and Flood.
+struct SplitMix64(u64);
+
+impl RngCore for SplitMix64 {
+	fn next_u32(&mut self) -> u32 {
+		(self.next_u64() >> 32) as u32
+	}
+
+	fn next_u64(&mut self) -> u64 {
+		self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+		let mut z = self.0;
+		z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+		z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+		z ^ (z >> 31)
+	}
+
+	fn fill_bytes(&mut self, dest: &mut [u8]) {
+		for chunk in dest.chunks_mut(8) {
+			let bytes = self.next_u64().to_le_bytes();
+			chunk.copy_from_slice(&bytes[..chunk.len()]);
+		}
+	}
+
+	fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+		self.fill_bytes(dest);
+		Ok(())
+	}
+}
+
+impl Default for Deck {
+	fn default() -> Self {
+		Self::new()
+	}
 }
 
 #[test]
@@ -24,4 +91,38 @@ fn test() {
 	assert_eq!(deck.cards[13], Ace.of(Hearts));
 	assert_eq!(deck.cards[30], Five.of(Clubs));
 	assert_eq!(deck.cards[51], King.of(Diamonds));
+
+	// The tests of other modules rely on these being the open cards of the
+	// columns, from left to right.
+	let open = [0, 1, 2, 3, 4, 5, 6].map(|c| deck.cards[ClosedState::position(c, c)]);
+	let expected = [
+		King.of(Diamonds),
+		Six.of(Diamonds),
+		King.of(Clubs),
+		Eight.of(Clubs),
+		Four.of(Clubs),
+		Ace.of(Clubs),
+		Queen.of(Hearts),
+	];
+	assert_eq!(open, expected);
+}
+
+#[test]
+fn test_from_seed() {
+	let mut rng = SplitMix64(0);
+	assert_eq!(rng.next_u64(), 0xE220_A839_7B1D_CDAF);
+	assert_eq!(rng.next_u64(), 0x6E78_9E6A_A1B9_65F4);
+
+	// These must never change, or old deal numbers would give different games.
+	let deck = Deck::from_seed(0);
+	assert_eq!(deck.cards[0], Eight.of(Diamonds));
+	assert_eq!(deck.cards[1], Jack.of(Clubs));
+	assert_eq!(deck.cards[2], Nine.of(Spades));
+	assert_eq!(deck.cards[24], Five.of(Hearts));
+	assert_eq!(deck.cards[51], Ten.of(Clubs));
+	let mut cards = deck.cards;
+	cards.sort();
+	assert_eq!(cards, Deck::new().cards);
+	assert_eq!(Deck::from_seed(0).cards, deck.cards);
+	assert_ne!(Deck::from_seed(1).cards, deck.cards);
 }
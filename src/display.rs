@@ -52,6 +52,80 @@ impl fmt::Display for Card {
 	}
 }
 
+/// Formats an action in the notation that `Action::from_str` accepts.
+///
+/// Columns are numbered 0 to 6. For example: `n` (next stock), `s-f`
+/// (stock to foundation), `s-3`, `3-f`, `fh-3` (top heart on the foundation
+/// to column 3), and `1-2x3` (three cards from column 1 to column 2).
+impl fmt::Display for Action {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match *self {
+			Action::NextStock => write!(f, "n"),
+			Action::StockToFoundation => write!(f, "s-f"),
+			Action::StockToStack(column) => write!(f, "s-{}", column),
+			Action::StackToFoundation(column) => write!(f, "{}-f", column),
+			Action::FoundationToStack(suit, column) => {
+				let c = match suit {
+					Spades => 's',
+					Hearts => 'h',
+					Clubs => 'c',
+					Diamonds => 'd',
+				};
+				write!(f, "f{}-{}", c, column)
+			}
+			Action::StackToStack { from, to, n } => write!(f, "{}-{}x{}", from, to, n),
+		}
+	}
+}
+
+impl fmt::Display for ActionError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		let s = match self {
+			ActionError::WrongColor => "card has the same colour as the card it would go on",
+			ActionError::WrongRank => "card has the wrong rank",
+			ActionError::NotKing => "only a King can go on an empty column",
+			ActionError::EmptySource => "there is no card to take",
+			ActionError::StockExhausted => "the stock is empty",
+			ActionError::NoPassesLeft => "no passes through the stock are left",
+			ActionError::InvalidCount => "invalid number of cards",
+			ActionError::SameColumn => "cannot move cards to the same column",
+			ActionError::InvalidColumn => "column does not exist",
+		};
+		f.write_str(s)
+	}
+}
+
+impl fmt::Display for RevealError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		let s = match self {
+			RevealError::NothingToReveal => "no card has been turned face up",
+			RevealError::AlreadySeen => "that card has been seen already",
+		};
+		f.write_str(s)
+	}
+}
+
+impl fmt::Display for AssistError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self {
+			AssistError::RevealPending => f.write_str("the card that was turned face up has to be revealed first"),
+			AssistError::Action(e) => e.fmt(f),
+		}
+	}
+}
+
+impl fmt::Display for ParseCardError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "invalid card")
+	}
+}
+
+impl fmt::Display for ParseActionError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "invalid action")
+	}
+}
+
 impl fmt::Debug for Card {
 	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
 		write!(f, "{:?} of {:?}", self.rank(), self.suit())
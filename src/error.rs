@@ -0,0 +1,76 @@
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum ActionError {
+	/// The card has the same colour as the card it would go on.
+	WrongColor,
+	/// The card does not have the rank needed where it would go.
+	WrongRank,
+	/// Only a King can go on an empty column.
+	NotKing,
+	/// There is no card to take.
+	EmptySource,
+	/// The stock has no cards left to turn over.
+	StockExhausted,
+	/// The rules allow no further passes through the stock.
+	NoPassesLeft,
+	/// The number of cards to move is zero or more than the stack holds.
+	InvalidCount,
+	/// The source and destination columns are the same.
+	SameColumn,
+	/// The column does not exist.
+	InvalidColumn,
+}
+
+impl std::error::Error for ActionError {}
+
+/// Why a card given to `Assistant::reveal` was not accepted.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum RevealError {
+	/// No card has been turned face up.
+	NothingToReveal,
+	/// The card is already somewhere else.
+	AlreadySeen,
+}
+
+impl std::error::Error for RevealError {}
+
+/// Why an action given to `Assistant::action` was not accepted.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum AssistError {
+	/// A card that was turned face up has to be revealed first.
+	RevealPending,
+	/// The action is not allowed.
+	Action(ActionError),
+}
+
+impl std::error::Error for AssistError {}
+
+impl From<ActionError> for AssistError {
+	fn from(e: ActionError) -> Self {
+		AssistError::Action(e)
+	}
+}
+
+#[test]
+fn test() {
+	use crate::*;
+
+	let deck = Deck::new();
+	let mut game = Game::new(&deck);
+	let stack_to_stack = |from, to, n| Action::StackToStack { from, to, n };
+	assert_eq!(game.action(stack_to_stack(1, 1, 1)), Err(ActionError::SameColumn));
+	assert_eq!(game.action(stack_to_stack(1, 7, 1)), Err(ActionError::InvalidColumn));
+	assert_eq!(game.action(stack_to_stack(6, 2, 0)), Err(ActionError::InvalidCount));
+	assert_eq!(game.action(stack_to_stack(6, 2, 2)), Err(ActionError::InvalidCount));
+	assert_eq!(game.action(stack_to_stack(6, 0, 1)), Err(ActionError::WrongColor));
+	assert_eq!(game.action(stack_to_stack(1, 4, 1)), Err(ActionError::WrongRank));
+	assert_eq!(game.action(Action::StackToFoundation(0)), Err(ActionError::WrongRank));
+	assert_eq!(game.action(Action::StockToFoundation), Err(ActionError::EmptySource));
+	assert_eq!(game.action(Action::FoundationToStack(Clubs, 0)), Err(ActionError::EmptySource));
+	assert_eq!(game.action(stack_to_stack(6, 2, 1)), Ok(()));
+	assert_eq!(game.action(Action::StackToFoundation(5)), Ok(()));
+	assert_eq!(game.action(Action::StackToFoundation(5)), Err(ActionError::WrongRank));
+	assert_eq!(game.action(Action::NextStock), Ok(()));
+	assert_eq!(game.action(Action::StockToStack(0)), Err(ActionError::WrongColor));
+	assert_eq!(game.action(Action::StockToStack(4)), Err(ActionError::WrongRank));
+	assert_eq!(ActionError::NotKing.to_string(), "only a King can go on an empty column");
+}
@@ -0,0 +1,65 @@
+use crate::*;
+
+/// Scores positions for the best-first search. Higher scores are explored first.
+pub trait Evaluator {
+	fn evaluate(&self, game: &Game) -> i32;
+}
+
+impl<F: Fn(&Game) -> i32> Evaluator for F {
+	fn evaluate(&self, game: &Game) -> i32 {
+		self(game)
+	}
+}
+
+/// A weighted sum of simple features of a position.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct DefaultEvaluator {
+	/// Points per card on the foundation.
+	pub foundation: i32,
+	/// Points per card that is still face down.
+	pub closed: i32,
+	/// Points per card in the stock.
+	pub stock: i32,
+	/// Points per empty column.
+	pub empty: i32,
+}
+
+impl DefaultEvaluator {
+	pub const fn new() -> Self {
+		Self {
+			foundation: 10,
+			closed: -8,
+			stock: -2,
+			empty: 3,
+		}
+	}
+}
+
+impl Default for DefaultEvaluator {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl Evaluator for DefaultEvaluator {
+	fn evaluate(&self, game: &Game) -> i32 {
+		let state = &game.state;
+		let empty = state.stacks.iter().filter(|s| s.is_empty()).count();
+		self.foundation * state.foundation.n_cards_total() as i32
+			+ self.closed * state.closed.n_closed_total() as i32
+			+ self.stock * state.stock.n_cards() as i32
+			+ self.empty * empty as i32
+	}
+}
+
+#[test]
+fn test() {
+	let deck = Deck::new();
+	let mut game = Game::new(&deck);
+	let e = DefaultEvaluator::new();
+	assert_eq!(e.evaluate(&game), -8 * 21 - 2 * 24);
+	game.action(Action::StackToFoundation(5)).unwrap();
+	assert_eq!(e.evaluate(&game), 10 - 8 * 20 - 2 * 24);
+	let foundation = |game: &Game| game.state.foundation.n_cards_total() as i32;
+	assert_eq!(foundation.evaluate(&game), 1);
+}
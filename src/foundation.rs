@@ -15,15 +15,38 @@ impl Foundation {
 		self.state == 0xDDDD
 	}
 
-	pub fn add(&mut self, card: Card) -> Result<(), ()> {
+	pub fn add(&mut self, card: Card) -> Result<(), ActionError> {
 		if card.rank().num() == self.n_cards(card.suit()) + 1 {
-			self.state += 1 << card.suit().num() * 4;
+			self.state += 1 << (card.suit().num() * 4);
 			Ok(())
 		} else {
-			Err(())
+			Err(ActionError::WrongRank)
 		}
 	}
 
+	/// Whether `card` can go on the foundation without ever being missed.
+	///
+	/// A card is only needed on a stack to hold a card of the opposite colour
+	/// that is one rank lower. This is the case when both of those are already
+	/// on the foundation, or when they are at most one rank away from it
+	/// while the other suit of this colour is close enough to take them.
+	pub fn is_safe(&self, card: Card) -> bool {
+		let rank = card.rank().num();
+		if rank != self.n_cards(card.suit()) + 1 {
+			return false;
+		}
+		let color = card.suit().color();
+		let opposite = Suit::all().filter(|s| s.color() != color).map(|s| self.n_cards(s)).min().unwrap();
+		let same = Suit::all().filter(|&s| s.color() == color && s != card.suit()).map(|s| self.n_cards(s)).sum::<usize>();
+		rank <= 2 || rank <= opposite + 1 || (rank <= opposite + 2 && rank <= same + 3)
+	}
+
+	pub fn take(&mut self, suit: Suit) -> Result<Card, ActionError> {
+		let card = self.top_card(suit).ok_or(ActionError::EmptySource)?;
+		self.state -= 1 << (suit.num() * 4);
+		Ok(card)
+	}
+
 	pub fn top_card(&self, suit: Suit) -> Option<Card> {
 		match self.n_cards(suit) {
 			0 => None,
@@ -46,6 +69,16 @@ impl Foundation {
 	pub fn n_cards(&self, suit: Suit) -> usize {
 		self.state.bits(suit.num() * 4..).bits(..4).into()
 	}
+
+	pub fn n_cards_total(&self) -> usize {
+		Suit::all().map(|suit| self.n_cards(suit)).sum()
+	}
+}
+
+impl Default for Foundation {
+	fn default() -> Self {
+		Self::new()
+	}
 }
 
 #[test]
@@ -64,7 +97,7 @@ fn test() {
 	assert_eq!(f.top_card(Clubs), None);
 	assert_eq!(f.top_card(Diamonds), None);
 	assert!(f.add(Ace.of(Hearts)).is_err());
-	assert!(f.add(Three.of(Hearts)).is_err());
+	assert_eq!(f.add(Three.of(Hearts)), Err(ActionError::WrongRank));
 	assert!(f.add(Two.of(Hearts)).is_ok());
 	assert!(f.add(Two.of(Spades)).is_err());
 	assert!(f.add(Ace.of(Spades)).is_ok());
@@ -74,6 +107,12 @@ fn test() {
 	assert_eq!(f.top_card(Diamonds), None);
 	assert!(f.contains(Two.of(Hearts)));
 	assert!(!f.contains(Three.of(Hearts)));
+	assert_eq!(f.n_cards_total(), 3);
+	assert!(!f.is_safe(Three.of(Hearts)));
+	assert!(f.is_safe(Two.of(Spades)));
+	assert!(!f.is_safe(Three.of(Spades)));
+	assert!(!f.is_safe(Ace.of(Hearts)));
+	assert!(f.is_safe(Ace.of(Clubs)));
 	for rank in Rank::all() {
 		let card = rank.of(Diamonds);
 		assert!(!f.contains(card));
@@ -82,4 +121,17 @@ fn test() {
 		assert_eq!(f.top_card(Diamonds), Some(card));
 		assert!(f.contains(card));
 	}
+	assert_eq!(f.n_cards_total(), 16);
+	assert!(!f.is_safe(Three.of(Hearts)));
+	assert!(f.add(Ace.of(Clubs)).is_ok());
+	assert!(f.is_safe(Three.of(Hearts)));
+	assert!(f.take(Clubs).is_ok());
+	assert_eq!(f.take(Diamonds), Ok(King.of(Diamonds)));
+	assert_eq!(f.take(Diamonds), Ok(Queen.of(Diamonds)));
+	assert_eq!(f.top_card(Diamonds), Some(Jack.of(Diamonds)));
+	assert_eq!(f.take(Clubs), Err(ActionError::EmptySource));
+	assert_eq!(f.take(Hearts), Ok(Two.of(Hearts)));
+	assert_eq!(f.take(Hearts), Ok(Ace.of(Hearts)));
+	assert_eq!(f.take(Hearts), Err(ActionError::EmptySource));
+	assert_eq!(f.n_cards_total(), 12);
 }
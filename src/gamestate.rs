@@ -7,6 +7,10 @@ pub struct GameState {
 	pub closed: ClosedState,
 	pub stacks: [Stack; 7],
 	pub foundation: Foundation,
+	/// The number of times the stock has been turned over.
+	///
+	/// Only counted when the rules limit the number of passes.
+	pub passes: u8,
 }
 
 #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
@@ -27,6 +31,7 @@ impl GameState {
 				Stack::single(deck.cards[24]),
 			],
 			foundation: Foundation::new(),
+			passes: 0,
 		}
 	}
 
@@ -41,10 +46,34 @@ impl GameState {
 		}
 		a.set_bits(56.., self.stock.state.bits(..8).into());
 		a.set_bits(62.., f.bits(14..).into());
-		b.set_bits(112.., self.closed.to_bits(0).into());
+		b.set_bits(112.., self.closed.to_bits(self.passes).into());
 		EncodedGameState(a, b)
 	}
 
+	/// Like `encode`, but ignoring the order of the columns without closed cards.
+	///
+	/// Positions that only differ in which of those columns holds which stack
+	/// play exactly the same, so they get the same key. This can only be used
+	/// to compare positions: decoding it gives a position with the columns in
+	/// a different order.
+	pub fn encode_canonical(&self) -> EncodedGameState {
+		let mut state = self.clone();
+		let mut open = [0; 7];
+		let mut n = 0;
+		for i in 0..7 {
+			if self.closed.n_closed(i) == 0 {
+				open[n] = i;
+				n += 1;
+			}
+		}
+		let mut stacks = open[..n].iter().map(|&i| self.stacks[i].clone()).collect::<Vec<_>>();
+		stacks.sort_by_key(|s| s.to_bits(0));
+		for (&i, stack) in open[..n].iter().zip(stacks) {
+			state.stacks[i] = stack;
+		}
+		state.encode()
+	}
+
 	pub fn decode(bits: EncodedGameState, deck: &Deck) -> Self {
 		let mut state = Self::new(deck);
 		let mut f = 0u16;
@@ -57,7 +86,9 @@ impl GameState {
 		}
 		f.set_bits(14.., bits.0.bits(62..) as u16);
 		state.foundation = Foundation::from_bits(f);
-		state.closed = ClosedState::from_bits(bits.1.bits(112..) as u16).0;
+		let (closed, passes) = ClosedState::from_bits(bits.1.bits(112..) as u16);
+		state.closed = closed;
+		state.passes = passes;
 		let mut stock = bits.0.bits(56..62) as u32;
 		for (i, &card) in deck.cards[..24].iter().enumerate() {
 			if
@@ -86,20 +117,49 @@ impl EncodedGameState {
 #[test]
 fn test_encode_decode() {
 	let deck = Deck::new();
-	let mut s = GameState::new();
+	let mut s = GameState::new(&deck);
 	assert_eq!(s, GameState::decode(s.encode(), &deck));
 	s.stock.next(3);
 	assert_eq!(s, GameState::decode(s.encode(), &deck));
-	s.stacks[1].add(s.stock.top_open_card(&deck).unwrap()).unwrap();
-	s.stock.take();
+	let card = s.stacks[6].take().unwrap();
+	s.stacks[2].add(card).unwrap();
+	s.stacks[6] = Stack::single(s.closed.open(6, &deck).unwrap());
 	assert_eq!(s, GameState::decode(s.encode(), &deck));
-	s.stock.next(3);
+	s.foundation.add(s.stacks[5].take().unwrap()).unwrap();
+	s.stacks[5] = Stack::single(s.closed.open(5, &deck).unwrap());
+	assert_eq!(s, GameState::decode(s.encode(), &deck));
+	s.stock.next(21);
+	assert_eq!(s.stock.top_open_card(&deck), Some(Ace.of(Spades)));
+	s.foundation.add(s.stock.top_open_card(&deck).unwrap()).unwrap();
+	s.stock.take().unwrap();
 	assert_eq!(s, GameState::decode(s.encode(), &deck));
-	s.stacks[2].add(s.closed.open(6, &deck).unwrap()).unwrap();
-	s.stacks[3].add(s.closed.open(6, &deck).unwrap()).unwrap();
+	s.foundation.add(s.stock.top_open_card(&deck).unwrap()).unwrap();
+	s.stock.take().unwrap();
 	assert_eq!(s, GameState::decode(s.encode(), &deck));
-	s.stacks[4].add(s.closed.open(6, &deck).unwrap()).unwrap();
-	s.stacks[5].add(s.closed.open(6, &deck).unwrap()).unwrap();
-	s.foundation.add(s.closed.open(6, &deck).unwrap()).unwrap();
+	s.passes = 3;
 	assert_eq!(s, GameState::decode(s.encode(), &deck));
 }
+
+#[test]
+fn test_encode_canonical() {
+	let deck = Deck::new();
+	let mut a = GameState::new(&deck);
+	a.closed = ClosedState::from_bits(0).0;
+	a.stacks[0] = Stack::single(King.of(Hearts));
+	a.stacks[1] = Stack::new();
+	a.stacks[3] = Stack::single(Eight.of(Clubs));
+	a.foundation.add(Ace.of(Spades)).unwrap();
+	let mut b = a.clone();
+	b.stacks.swap(0, 1);
+	b.stacks.swap(3, 6);
+	assert_ne!(a.encode(), b.encode());
+	assert_eq!(a.encode_canonical(), b.encode_canonical());
+	b.foundation.take(Spades).unwrap();
+	assert_ne!(a.encode_canonical(), b.encode_canonical());
+
+	// Columns with closed cards keep their place.
+	let c = GameState::new(&deck);
+	let mut d = c.clone();
+	d.stacks.swap(1, 2);
+	assert_ne!(c.encode_canonical(), d.encode_canonical());
+}
@@ -0,0 +1,48 @@
+use crate::*;
+
+impl Game<'_> {
+	/// Suggests a move for the current position.
+	///
+	/// Searches at most `budget` positions for a solution and returns its
+	/// first move. If none is found, returns the move that leads to the
+	/// position `DefaultEvaluator` likes best. Returns `None` if the game is
+	/// already won or no move is possible.
+	pub fn hint(&self, budget: usize) -> Option<Action> {
+		if let Outcome::Solved(actions) = Solver::new().max_nodes(budget).solve(self).outcome {
+			return actions.first().copied();
+		}
+		let evaluator = DefaultEvaluator::new();
+		let mut best: Option<(i32, Action)> = None;
+		self.for_all_possible_actions(|action| {
+			let mut game = self.clone();
+			if game.action(action).is_ok() {
+				let score = evaluator.evaluate(&game);
+				if best.is_none_or(|(s, _)| score > s) {
+					best = Some((score, action));
+				}
+			}
+			false
+		});
+		best.map(|(_, action)| action)
+	}
+}
+
+#[test]
+fn test() {
+	let deck = Deck::new();
+	let mut game = Game::new(&deck);
+	for _ in 0..1000 {
+		match game.hint(10_000) {
+			Some(action) => game.action(action).unwrap(),
+			None => break,
+		}
+	}
+	assert!(game.state.foundation.is_complete());
+	assert_eq!(game.hint(10_000), None);
+
+	// Without a budget, the hint must still be a legal move.
+	let deck = Deck::from_seed(1);
+	let game = Game::new(&deck);
+	let action = game.hint(0).unwrap();
+	assert!(game.clone().action(action).is_ok());
+}
@@ -0,0 +1,223 @@
+use crate::*;
+
+/// An action that was applied to a game, along with what it revealed.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Move {
+	pub action: Action,
+	/// The closed card that was turned face up by this action, if any.
+	pub revealed: Option<Card>,
+	/// Whether this was a safe move played automatically after another move.
+	pub auto: bool,
+	card: Option<Card>,
+	stock: Stock,
+	passes: u8,
+}
+
+/// A game that remembers its moves, so they can be undone and redone.
+#[derive(Clone, Debug)]
+pub struct GameHistory<'a> {
+	game: Game<'a>,
+	done: Vec<Move>,
+	undone: Vec<Action>,
+}
+
+impl<'a> GameHistory<'a> {
+	pub fn new(game: Game<'a>) -> Self {
+		Self {
+			game,
+			done: Vec::new(),
+			undone: Vec::new(),
+		}
+	}
+
+	pub fn game(&self) -> &Game<'a> {
+		&self.game
+	}
+
+	pub fn moves(&self) -> &[Move] {
+		&self.done
+	}
+
+	/// Applies an action, forgetting all moves that were undone.
+	pub fn action(&mut self, action: Action) -> Result<(), ActionError> {
+		self.apply(action)?;
+		self.undone.clear();
+		Ok(())
+	}
+
+	/// Undoes the last action, along with the safe moves played after it.
+	pub fn undo(&mut self) -> Option<Move> {
+		loop {
+			let m = self.done.pop()?;
+			self.revert(&m);
+			if !m.auto {
+				self.undone.push(m.action);
+				return Some(m);
+			}
+		}
+	}
+
+	pub fn redo(&mut self) -> Option<Move> {
+		let action = self.undone.pop()?;
+		let n = self.done.len();
+		self.apply(action).expect("redo of an action that was valid before");
+		Some(self.done[n])
+	}
+
+	fn apply(&mut self, action: Action) -> Result<(), ActionError> {
+		self.record(action, false)?;
+		if self.game.rules.auto_foundation {
+			while let Some(action) = self.game.safe_move() {
+				self.record(action, true).unwrap();
+			}
+		}
+		Ok(())
+	}
+
+	fn record(&mut self, action: Action, auto: bool) -> Result<(), ActionError> {
+		let state = &self.game.state;
+		let card = match action {
+			Action::NextStock => None,
+			Action::StockToFoundation | Action::StockToStack(_) => {
+				state.stock.top_open_card(self.game.deck)
+			}
+			Action::StackToFoundation(column) => {
+				state.stacks.get(usize::from(column)).and_then(Stack::last)
+			}
+			Action::FoundationToStack(suit, _) => state.foundation.top_card(suit),
+			Action::StackToStack { from, n, .. } => {
+				state.stacks.get(usize::from(from)).and_then(|s| {
+					s.get(s.len().checked_sub(usize::from(n))?)
+				})
+			}
+		};
+		let stock = state.stock;
+		let passes = state.passes;
+		let closed = state.closed.clone();
+		self.game.apply(action)?;
+		let revealed = (0..7)
+			.find(|&i| self.game.state.closed.n_closed(i) != closed.n_closed(i))
+			.map(|i| self.game.state.stacks[i].get(0).unwrap());
+		self.done.push(Move { action, revealed, auto, card, stock, passes });
+		Ok(())
+	}
+
+	fn revert(&mut self, m: &Move) {
+		let state = &mut self.game.state;
+		let card = m.card;
+		match m.action {
+			Action::NextStock => {}
+			Action::StockToFoundation => {
+				state.foundation.take(card.unwrap().suit()).unwrap();
+			}
+			Action::StockToStack(column) => {
+				state.stacks[usize::from(column)].take().unwrap();
+			}
+			Action::StackToFoundation(column) => {
+				let column = usize::from(column);
+				let card = state.foundation.take(card.unwrap().suit()).unwrap();
+				if m.revealed.is_some() {
+					state.closed.close(column);
+					state.stacks[column] = Stack::new();
+				}
+				put_back(&mut state.stacks[column], &[card]);
+			}
+			Action::FoundationToStack(_, column) => {
+				let card = state.stacks[usize::from(column)].take().unwrap();
+				state.foundation.add(card).unwrap();
+			}
+			Action::StackToStack { from, to, n } => {
+				let (from, to) = (usize::from(from), usize::from(to));
+				let mut cards = Vec::with_capacity(usize::from(n));
+				for _ in 0..n {
+					cards.push(state.stacks[to].take().unwrap());
+				}
+				cards.reverse();
+				if m.revealed.is_some() {
+					state.closed.close(from);
+					state.stacks[from] = Stack::new();
+				}
+				put_back(&mut state.stacks[from], &cards);
+			}
+		}
+		state.stock = m.stock;
+		state.passes = m.passes;
+	}
+}
+
+/// Puts cards back on a stack, even if the first one isn't a King.
+fn put_back(stack: &mut Stack, cards: &[Card]) {
+	for &card in cards {
+		if stack.is_empty() {
+			*stack = Stack::single(card);
+		} else {
+			stack.add(card).unwrap();
+		}
+	}
+}
+
+#[test]
+fn test() {
+	let deck = Deck::new();
+	let game = Game::with_rules(&deck, Rules::vegas_draw_three());
+	let actions = Solver::new().solve(&game).outcome.unwrap_solved();
+
+	let mut history = GameHistory::new(game);
+	let mut states = vec![history.game().state.clone()];
+	for &action in &actions {
+		history.action(action).unwrap();
+		states.push(history.game().state.clone());
+	}
+	assert!(history.game().state.foundation.is_complete());
+	assert_eq!(history.moves().len(), actions.len());
+	assert!(history.moves().iter().any(|m| m.revealed.is_some()));
+	assert_eq!(history.moves().iter().filter_map(|m| m.revealed).count(), 21);
+
+	for (i, &action) in actions.iter().enumerate().rev() {
+		assert_eq!(history.undo().unwrap().action, action);
+		assert_eq!(history.game().state, states[i]);
+	}
+	assert_eq!(history.undo(), None);
+
+	for (i, &action) in actions.iter().enumerate() {
+		assert_eq!(history.redo().unwrap().action, action);
+		assert_eq!(history.game().state, states[i + 1]);
+	}
+	assert_eq!(history.redo(), None);
+
+	history.undo().unwrap();
+	history.undo().unwrap();
+	history.action(actions[actions.len() - 2]).unwrap();
+	assert_eq!(history.redo(), None);
+	assert_eq!(history.game().state, states[actions.len() - 1]);
+
+	use rand::{seq::SliceRandom, Rng, SeedableRng};
+	let mut rng = rand::rngs::StdRng::seed_from_u64(0);
+	for i in 0..20 {
+		let mut deck = Deck::new();
+		deck.cards.shuffle(&mut rng);
+		let rules = Rules { auto_foundation: i % 2 == 0, ..Rules::vegas_draw_one() };
+		let mut history = GameHistory::new(Game::with_rules(&deck, rules));
+		let mut states = vec![history.game().state.clone()];
+		for _ in 0..200 {
+			let mut actions = Vec::new();
+			history.game().for_all_possible_actions(|action| {
+				actions.push(action);
+				false
+			});
+			if actions.is_empty() {
+				break;
+			}
+			let action = actions[rng.gen_range(0, actions.len())];
+			if history.action(action).is_ok() {
+				states.push(history.game().state.clone());
+			}
+		}
+		states.pop();
+		while let Some(state) = states.pop() {
+			history.undo().unwrap();
+			assert_eq!(history.game().state, state);
+		}
+		assert_eq!(history.undo(), None);
+	}
+}
@@ -1,80 +1,170 @@
+mod assistant;
 mod card;
 mod closed;
 mod deck;
 mod display;
+mod error;
+mod evaluator;
 mod foundation;
 mod gamestate;
+mod hint;
+mod history;
+mod montecarlo;
+mod parallel;
+mod player;
 mod rank;
+mod rules;
+mod solver;
 mod stack;
 mod stock;
 mod suit;
+mod verify;
+mod winrate;
 
+pub use assistant::Assistant;
 pub use card::*;
 pub use closed::ClosedState;
 pub use deck::Deck;
+pub use error::{ActionError, AssistError, RevealError};
+pub use evaluator::{DefaultEvaluator, Evaluator};
 pub use foundation::Foundation;
 pub use gamestate::{EncodedGameState, GameState};
+pub use history::{GameHistory, Move};
+pub use montecarlo::MonteCarloAgent;
+pub use player::{play_game, Agent, GreedyAgent, PlayerView};
 pub use rank::*;
+pub use rules::Rules;
+pub use solver::{CancelToken, Outcome, Progress, SolveResult, Solver};
 pub use stack::Stack;
 pub use stock::Stock;
 pub use suit::*;
+pub use verify::{verify, FinalState};
+pub use winrate::{win_rate, wilson, Deal, WinRate, CSV_HEADER};
 
 #[derive(Clone, Debug)]
 pub struct Game<'a> {
 	pub deck: &'a Deck,
+	pub rules: Rules,
 	pub state: GameState,
 }
 
 impl<'a> Game<'a> {
 	pub const fn new(deck: &'a Deck) -> Self {
+		Self::with_rules(deck, Rules::new())
+	}
+
+	pub const fn with_rules(deck: &'a Deck, rules: Rules) -> Self {
+		assert!(rules.draw > 0);
+		if let Some(passes) = rules.passes {
+			assert!(passes > 0 && passes <= 4);
+		}
 		Self {
 			deck,
+			rules,
 			state: GameState::new(deck),
 		}
 	}
 
-	pub fn action(&mut self, action: Action) -> Result<(), ()> {
+	/// Applies an action, and then any safe moves if the rules say so.
+	pub fn action(&mut self, action: Action) -> Result<(), ActionError> {
+		self.action_with(action, |_| ())
+	}
+
+	/// Like `action`, but calls `f` after every single move, including the safe moves.
+	pub fn action_with(&mut self, action: Action, mut f: impl FnMut(&Self)) -> Result<(), ActionError> {
+		self.apply(action)?;
+		f(self);
+		if self.rules.auto_foundation {
+			while let Some(action) = self.safe_move() {
+				self.apply(action).unwrap();
+				f(self);
+			}
+		}
+		Ok(())
+	}
+
+	/// Moves a card to the foundation if that is always safe.
+	///
+	/// See `Foundation::is_safe`. Cards from the stock are only moved when
+	/// drawing one card at a time: when drawing more, taking a card changes
+	/// which cards of the stock can be reached later.
+	pub fn safe_move(&self) -> Option<Action> {
+		let foundation = &self.state.foundation;
+		if self.rules.draw == 1 {
+			if let Some(card) = self.state.stock.top_open_card(self.deck) {
+				if foundation.is_safe(card) {
+					return Some(Action::StockToFoundation);
+				}
+			}
+		}
+		(0..7).find(|&i| {
+			self.state.stacks[usize::from(i)].last().is_some_and(|card| foundation.is_safe(card))
+		}).map(Action::StackToFoundation)
+	}
+
+	/// Applies a single action, without any safe moves afterwards.
+	pub(crate) fn apply(&mut self, action: Action) -> Result<(), ActionError> {
 		match action {
 			Action::NextStock => {
 				if self.state.stock.is_empty() {
-					Err(())
-				} else {
-					self.state.stock.next(3);
-					Ok(())
+					return Err(ActionError::StockExhausted);
+				}
+				if self.state.stock.n_closed_cards() == 0 {
+					if let Some(passes) = self.rules.passes {
+						if self.state.passes + 1 >= passes {
+							return Err(ActionError::NoPassesLeft);
+						}
+						self.state.passes += 1;
+					}
 				}
+				self.state.stock.next(usize::from(self.rules.draw));
+				Ok(())
 			}
 			Action::StockToFoundation => {
-				let card = self.state.stock.top_open_card(&self.deck).ok_or(())?;
+				let card = self.state.stock.top_open_card(self.deck).ok_or(ActionError::EmptySource)?;
 				self.state.foundation.add(card)?;
 				self.state.stock.take().unwrap();
 				Ok(())
 			}
 			Action::StockToStack(column) => {
-				let card = self.state.stock.top_open_card(&self.deck).ok_or(())?;
+				let card = self.state.stock.top_open_card(self.deck).ok_or(ActionError::EmptySource)?;
+				column_index(column)?;
 				self.state.stacks[usize::from(column)].add(card)?;
 				self.state.stock.take().unwrap();
 				Ok(())
 			}
 			Action::StackToFoundation(column) => {
-				let stack = &mut self.state.stacks[usize::from(column)];
-				let card = stack.last().ok_or(())?;
+				let stack = &mut self.state.stacks[column_index(column)?];
+				let card = stack.last().ok_or(ActionError::EmptySource)?;
 				self.state.foundation.add(card)?;
 				stack.take().unwrap();
-				if stack.len() == 0 {
+				if stack.is_empty() {
 					if let Ok(card) = self.state.closed.open(usize::from(column), self.deck) {
 						*stack = Stack::single(card);
 					}
 				}
 				Ok(())
 			}
+			Action::FoundationToStack(suit, column) => {
+				let card = self.state.foundation.top_card(suit).ok_or(ActionError::EmptySource)?;
+				self.state.stacks[column_index(column)?].add(card)?;
+				self.state.foundation.take(suit).unwrap();
+				Ok(())
+			}
 			Action::StackToStack { from, to, n } => {
-				assert_ne!(from, to);
-				let from_col = usize::from(from);
+				let from_col = column_index(from)?;
+				let to_col = column_index(to)?;
+				if from_col == to_col {
+					return Err(ActionError::SameColumn);
+				}
 				// Safe, because `to` and `from` are different.
 				let from = unsafe { &mut *self.state.stacks.as_mut_ptr().add(from_col) };
-				let to = unsafe { &mut *self.state.stacks.as_mut_ptr().add(usize::from(to)) };
+				let to = unsafe { &mut *self.state.stacks.as_mut_ptr().add(to_col) };
 				let n = usize::from(n);
-				let offset = from.len().checked_sub(n).ok_or(())?;
+				if n == 0 {
+					return Err(ActionError::InvalidCount);
+				}
+				let offset = from.len().checked_sub(n).ok_or(ActionError::InvalidCount)?;
 				let card = from.get(offset).unwrap();
 				to.add(card)?;
 				for i in 1..n {
@@ -83,7 +173,7 @@ impl<'a> Game<'a> {
 				for _ in 0..n {
 					from.take().unwrap();
 				}
-				if from.len() == 0 {
+				if from.is_empty() {
 					if let Ok(card) = self.state.closed.open(from_col, self.deck) {
 						*from = Stack::single(card);
 					}
@@ -93,6 +183,17 @@ impl<'a> Game<'a> {
 		}
 	}
 
+	/// Like `for_all_possible_actions`, but only gives the safe move if there is one.
+	///
+	/// A safe move is never worse than any other move, so a search doesn't
+	/// have to bother with the rest.
+	pub(crate) fn for_all_search_actions(&self, mut f: impl FnMut(Action) -> bool) -> bool {
+		match self.safe_move() {
+			Some(action) => f(action),
+			None => self.for_all_possible_actions(f),
+		}
+	}
+
 	pub fn for_all_possible_actions(&self, mut f: impl FnMut(Action) -> bool) -> bool {
 		if f(Action::StockToFoundation) { return true; }
 
@@ -109,19 +210,70 @@ impl<'a> Game<'a> {
 		for from in 0..7 {
 			for to in 0..7 {
 				if from == to { continue; }
-				//for n in 1..=self.state.stacks[usize::from(from)].len() as u8 {
-				let n = self.state.stacks[usize::from(from)].len() as u8;
-				let m = self.state.stacks[usize::from(to)].len();
-				if m == 0 && self.state.closed.n_closed(usize::from(from)) == 0 {
-					// nope
-				} else {
-					if n > 0 && f(Action::StackToStack { from, to, n }) { return true; }
+				let source = &self.state.stacks[usize::from(from)];
+				let target = &self.state.stacks[usize::from(to)];
+				let n = match source.movable_onto(target) {
+					Some(n) => n,
+					None => continue,
+				};
+				if n < source.len() {
+					// Only split a run if the card it uncovers can then be used.
+					let uncovered = source.get(source.len() - n - 1).unwrap();
+					if !self.is_useful(uncovered, from, to) { continue; }
+				} else if target.is_empty() && self.state.closed.n_closed(usize::from(from)) == 0 {
+					// Moving an entire column to an empty column changes nothing.
+					continue;
+				}
+				if f(Action::StackToStack { from, to, n: n as u8 }) { return true; }
+			}
+		}
+
+		for suit in Suit::all() {
+			if let Some(card) = self.state.foundation.top_card(suit) {
+				for to in 0..7 {
+					if
+						self.state.stacks[usize::from(to)].can_add(card) &&
+						f(Action::FoundationToStack(suit, to))
+					{
+						return true;
+					}
 				}
 			}
 		}
 
 		false
 	}
+
+	/// Whether an open card could be used once it is on top of its stack: either
+	/// it can go to the foundation, or some other card can be put on it.
+	///
+	/// Only considers cards from the foundation, the stock, and stacks other than
+	/// `from` and `to`.
+	fn is_useful(&self, card: Card, from: u8, to: u8) -> bool {
+		if self.state.foundation.n_cards(card.suit()) + 1 == card.rank().num() {
+			return true;
+		}
+		let target = Stack::single(card);
+		if let Some(c) = self.state.stock.top_open_card(self.deck) {
+			if target.can_add(c) {
+				return true;
+			}
+		}
+		if Suit::all().filter_map(|suit| self.state.foundation.top_card(suit)).any(|c| target.can_add(c)) {
+			return true;
+		}
+		(0..7).filter(|&i| i != from && i != to).any(|i| {
+			self.state.stacks[usize::from(i)].movable_onto(&target).is_some()
+		})
+	}
+}
+
+fn column_index(column: u8) -> Result<usize, ActionError> {
+	if column < 7 {
+		Ok(usize::from(column))
+	} else {
+		Err(ActionError::InvalidColumn)
+	}
 }
 
 #[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
@@ -130,9 +282,142 @@ pub enum Action {
 	StockToFoundation,
 	StockToStack(u8),
 	StackToFoundation(u8),
+	FoundationToStack(Suit, u8),
 	StackToStack {
 		from: u8,
 		to: u8,
 		n: u8,
 	}
 }
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct ParseActionError;
+
+impl std::error::Error for ParseActionError {}
+
+impl std::str::FromStr for Action {
+	type Err = ParseActionError;
+
+	fn from_str(s: &str) -> Result<Self, ParseActionError> {
+		let column = |s: &str| match s.parse() {
+			Ok(column) if column < 7 => Ok(column),
+			_ => Err(ParseActionError),
+		};
+		if s == "n" {
+			return Ok(Action::NextStock);
+		}
+		let (from, to) = s.split_at(s.find('-').ok_or(ParseActionError)?);
+		let to = &to[1..];
+		match (from, to) {
+			("s", "f") => Ok(Action::StockToFoundation),
+			("s", to) => Ok(Action::StockToStack(column(to)?)),
+			("fs", to) => Ok(Action::FoundationToStack(Spades, column(to)?)),
+			("fh", to) => Ok(Action::FoundationToStack(Hearts, column(to)?)),
+			("fc", to) => Ok(Action::FoundationToStack(Clubs, column(to)?)),
+			("fd", to) => Ok(Action::FoundationToStack(Diamonds, column(to)?)),
+			(from, "f") => Ok(Action::StackToFoundation(column(from)?)),
+			(from, to) => {
+				let (to, n) = to.split_at(to.find('x').ok_or(ParseActionError)?);
+				Ok(Action::StackToStack {
+					from: column(from)?,
+					to: column(to)?,
+					n: n[1..].parse().map_err(|_| ParseActionError)?,
+				})
+			}
+		}
+	}
+}
+
+#[test]
+fn test_possible_actions() {
+	let deck = Deck::new();
+	let game = Game::new(&deck);
+	let mut legal = Vec::new();
+	game.for_all_possible_actions(|action| {
+		if game.clone().action(action).is_ok() {
+			legal.push(action);
+		}
+		false
+	});
+	assert!(legal.contains(&Action::StackToFoundation(5)));
+	assert!(legal.contains(&Action::NextStock));
+	let mut search = Vec::new();
+	game.for_all_search_actions(|action| {
+		search.push(action);
+		false
+	});
+	assert_eq!(search, [Action::StackToFoundation(5)]);
+
+	// Moving ♣J from ♥Q to ♦Q is only offered once something can go on ♥Q.
+	let mut game = Game::new(&deck);
+	game.state.stacks = Default::default();
+	game.state.stacks[0] = Stack::single(King.of(Spades));
+	game.state.stacks[0].add(Queen.of(Hearts)).unwrap();
+	game.state.stacks[0].add(Jack.of(Clubs)).unwrap();
+	game.state.stacks[1] = Stack::single(Queen.of(Diamonds));
+	let split = Action::StackToStack { from: 0, to: 1, n: 1 };
+	let offers_split = |game: &Game| {
+		let mut found = false;
+		game.for_all_possible_actions(|action| {
+			found |= action == split;
+			false
+		});
+		found
+	};
+	assert!(!offers_split(&game));
+	let mut from_stack = game.clone();
+	from_stack.state.stacks[2] = Stack::single(Jack.of(Spades));
+	assert!(offers_split(&from_stack));
+	let mut from_foundation = game.clone();
+	for rank in Rank::all().take(11) {
+		from_foundation.state.foundation.add(rank.of(Spades)).unwrap();
+	}
+	assert!(offers_split(&from_foundation));
+	assert!(from_foundation.clone().action(split).is_ok());
+}
+
+#[test]
+fn test_foundation_to_stack() {
+	let deck = Deck::new();
+	let mut game = Game::new(&deck);
+	game.state.stacks = Default::default();
+	game.state.stacks[0] = Stack::single(Two.of(Clubs));
+	game.state.stacks[1] = Stack::single(Three.of(Hearts));
+	game.state.foundation.add(Ace.of(Clubs)).unwrap();
+	game.action(Action::StackToFoundation(0)).unwrap();
+	assert_eq!(game.state.foundation.top_card(Clubs), Some(Two.of(Clubs)));
+
+	let action = Action::FoundationToStack(Clubs, 1);
+	let mut possible = Vec::new();
+	game.for_all_possible_actions(|action| {
+		possible.push(action);
+		false
+	});
+	assert!(possible.contains(&action));
+	game.action(action).unwrap();
+	assert!(game.state.stacks[0].is_empty());
+	assert_eq!(game.state.stacks[1].len(), 2);
+	assert_eq!(game.state.stacks[1].last(), Some(Two.of(Clubs)));
+	assert_eq!(game.state.foundation.top_card(Clubs), Some(Ace.of(Clubs)));
+	assert!(game.action(Action::FoundationToStack(Clubs, 1)).is_err());
+}
+
+#[test]
+fn test_notation() {
+	let actions = [
+		Action::NextStock,
+		Action::StockToFoundation,
+		Action::StockToStack(3),
+		Action::StackToFoundation(0),
+		Action::FoundationToStack(Hearts, 6),
+		Action::StackToStack { from: 1, to: 2, n: 11 },
+	];
+	for &action in &actions {
+		assert_eq!(action.to_string().parse(), Ok(action));
+	}
+	assert_eq!("1-2x3".parse(), Ok(Action::StackToStack { from: 1, to: 2, n: 3 }));
+	assert_eq!("fd-4".parse(), Ok(Action::FoundationToStack(Diamonds, 4)));
+	for &s in &["", "x", "s", "s-7", "1-2", "1-2x", "f-3", "fx-3", "7-f", "-1x1"] {
+		assert_eq!(s.parse::<Action>(), Err(ParseActionError));
+	}
+}
@@ -1,84 +1,470 @@
-use std::collections::HashSet;
-use rand::seq::SliceRandom;
+use std::io::{self, BufRead, Write};
+use std::process::exit;
 use klondike::*;
 
-fn main() {
-	let mut deck = Deck::new();
-	deck.cards.shuffle(&mut rand::thread_rng());
+const USAGE: &str = "\
+Usage: klondike <command> [options]
 
-	let mut game = Game::new(&deck);
+Commands:
+    solve       Solve a deal
+    play        Play a deal interactively
+    generate    Print the cards of one or more deals
+    stats       Solve a range of deals and count the outcomes
+    assist      Give hints for a game played elsewhere, entering cards as
+                they are turned face up
 
-	print_board(&game);
+Options:
+    --seed N          Deal number (default: random)
+    --seeds A..B      Range of deal numbers, for stats (default: 0..100)
+    --count N         Number of deals to generate (default: 1)
+    --draw N          Cards turned per stock flip (default: 3)
+    --passes N        Maximum passes through the stock (default: unlimited)
+    --auto            Automatically move cards to the foundation when safe
+    --max-nodes N     Give up after expanding N positions
+    --max-depth N     Give up on lines longer than N moves (default: 500)
+    --max-seen N      Give up after remembering N positions
+    --time-limit S    Give up after S seconds per deal
+    --best-first      Explore the most promising positions first
+    --shortest        Find a solution with as few moves as possible, among
+                      the moves the solver tries
+    --threads N       Number of threads to solve with (default: 1)
+    --progress        Report progress on stderr while solving
+    --json            Print machine-readable JSON instead of text
+    --csv FILE        Write the result of every deal to FILE, for stats
+    --agent NAME      Have greedy or pimc play the deals for stats, only
+                      seeing face-up cards, instead of solving them
+    --samples N       Decks sampled per move by pimc and assist (default: 10)
+    --max-moves N     Moves an agent may make per deal (default: 1000)
+    --verbose         Print the board after every move of a solution
+";
 
-	let mut e = Explorer::default();
+struct Options {
+	seed: u64,
+	seeds: std::ops::Range<u64>,
+	count: u64,
+	rules: Rules,
+	max_nodes: Option<usize>,
+	max_depth: Option<usize>,
+	max_seen: Option<usize>,
+	time_limit: Option<f64>,
+	best_first: bool,
+	shortest: bool,
+	threads: usize,
+	progress: bool,
+	json: bool,
+	csv: Option<String>,
+	agent: Option<String>,
+	samples: usize,
+	max_moves: usize,
+	verbose: bool,
+}
 
-	if e.explore(&game, 0) {
-		println!("Won!");
-	} else {
-		println!("Didn't win. :(");
-		println!("Closest I got:");
+impl Options {
+	fn parse(mut args: impl Iterator<Item = String>) -> Result<Self, String> {
+		let mut options = Options {
+			seed: rand::random(),
+			seeds: 0..100,
+			count: 1,
+			rules: Rules::new(),
+			max_nodes: None,
+			max_depth: None,
+			max_seen: None,
+			time_limit: None,
+			best_first: false,
+			shortest: false,
+			threads: 1,
+			progress: false,
+			json: false,
+			csv: None,
+			agent: None,
+			samples: 10,
+			max_moves: 1000,
+			verbose: false,
+		};
+		while let Some(arg) = args.next() {
+			let mut value = || args.next().ok_or_else(|| format!("missing value for {}", arg));
+			match arg.as_str() {
+				"--seed" => options.seed = parse(&value()?)?,
+				"--seeds" => {
+					let value = value()?;
+					let (a, b) = value.split_at(value.find("..").ok_or("expected a range like 0..100")?);
+					options.seeds = parse(a)?..parse(&b[2..])?;
+				}
+				"--count" => options.count = parse(&value()?)?,
+				"--draw" => options.rules.draw = parse(&value()?)?,
+				"--passes" => options.rules.passes = Some(parse(&value()?)?),
+				"--auto" => options.rules.auto_foundation = true,
+				"--max-nodes" => options.max_nodes = Some(parse(&value()?)?),
+				"--max-depth" => options.max_depth = Some(parse(&value()?)?),
+				"--max-seen" => options.max_seen = Some(parse(&value()?)?),
+				"--time-limit" => options.time_limit = Some(parse(&value()?)?),
+				"--best-first" => options.best_first = true,
+				"--shortest" => options.shortest = true,
+				"--threads" => options.threads = parse(&value()?)?,
+				"--progress" => options.progress = true,
+				"--json" => options.json = true,
+				"--csv" => options.csv = Some(value()?),
+				"--agent" => options.agent = Some(value()?),
+				"--samples" => options.samples = parse(&value()?)?,
+				"--max-moves" => options.max_moves = parse(&value()?)?,
+				"--verbose" => options.verbose = true,
+				_ => return Err(format!("unknown option {}", arg)),
+			}
+		}
+		if options.time_limit.is_some_and(|t| !(0.0..1e9).contains(&t)) {
+			return Err("invalid --time-limit".into());
+		}
+		if options.agent.as_ref().is_some_and(|a| a != "greedy" && a != "pimc") {
+			return Err("--agent must be greedy or pimc".into());
+		}
+		if options.threads == 0 {
+			return Err("--threads must be at least 1".into());
+		}
+		if options.rules.draw == 0 {
+			return Err("--draw must be at least 1".into());
+		}
+		if options.rules.passes.is_some_and(|p| p == 0 || p > 4) {
+			return Err("--passes must be between 1 and 4".into());
+		}
+		Ok(options)
+	}
 
-		let s = e.seen.iter().max_by_key(|s| {
-			let f = s.get_foundation();
-			f.n_cards(Spades) + f.n_cards(Hearts) + f.n_cards(Clubs) + f.n_cards(Diamonds)
-		});
+	fn solver(&self) -> Solver {
+		let mut solver = Solver::new().threads(self.threads);
+		if let Some(n) = self.max_nodes {
+			solver = solver.max_nodes(n);
+		}
+		if let Some(n) = self.max_depth {
+			solver = solver.max_depth(n);
+		}
+		if let Some(n) = self.max_seen {
+			solver = solver.max_seen(n);
+		}
+		if let Some(t) = self.time_limit {
+			solver = solver.time_limit(std::time::Duration::from_secs_f64(t));
+		}
+		if self.best_first {
+			solver = solver.best_first(DefaultEvaluator::new());
+		}
+		if self.shortest {
+			solver = solver.shortest();
+		}
+		solver
+	}
+}
 
-		game.state = GameState::decode(*s.unwrap(), &game.deck);
+fn parse<T: std::str::FromStr>(s: &str) -> Result<T, String> {
+	s.parse().map_err(|_| format!("invalid number {:?}", s))
+}
 
-		print_board(&game);
+fn main() {
+	let mut args = std::env::args().skip(1);
+	let command = args.next().unwrap_or_default();
+	let options = match Options::parse(args) {
+		Ok(options) => options,
+		Err(e) => {
+			eprintln!("klondike: {}\n\n{}", e, USAGE);
+			exit(2);
+		}
+	};
+	match command.as_str() {
+		"solve" => solve(&options),
+		"play" => play(&options),
+		"generate" => generate(&options),
+		"stats" => stats(&options),
+		"assist" => assist(&options),
+		"help" | "--help" | "-h" => print!("{}", USAGE),
+		_ => {
+			eprint!("{}", USAGE);
+			exit(2);
+		}
 	}
+}
 
-	dbg!(e.seen.len());
+fn outcome_name(outcome: &Outcome) -> &'static str {
+	match outcome {
+		Outcome::Solved(_) => "solved",
+		Outcome::Unsolvable => "unsolvable",
+		Outcome::Unknown => "unknown",
+	}
+}
+
+fn json_rules(rules: &Rules) -> String {
+	match rules.passes {
+		Some(passes) => format!("\"draw\":{},\"passes\":{}", rules.draw, passes),
+		None => format!("\"draw\":{},\"passes\":null", rules.draw),
+	}
 }
 
-#[derive(Default, Debug)]
-struct Explorer {
-	seen: HashSet<EncodedGameState>,
-	actions: Vec<(EncodedGameState, Action)>,
+fn json_actions(actions: &[Action]) -> String {
+	let actions: Vec<String> = actions.iter().map(|a| format!("\"{}\"", a)).collect();
+	format!("[{}]", actions.join(","))
 }
 
-impl Explorer {
-	fn explore(&mut self, game: &Game, depth: usize) -> bool {
-		if depth > 500 {
-			for (i, &(e, a)) in self.actions.iter().enumerate() {
-				println!("{}: {:?}", i, a);
-				let game = Game {
-					deck: game.deck,
-					state: GameState::decode(e, &game.deck),
-				};
-				print_board(&game);
+fn solve(options: &Options) {
+	let deck = Deck::from_seed(options.seed);
+	let mut game = Game::with_rules(&deck, options.rules);
+	let mut solver = options.solver();
+	if options.progress {
+		solver = solver.on_progress(std::time::Duration::from_millis(500), |p| {
+			eprintln!(
+				"{:.1}s: {} positions, depth {}, {} seen, best foundation {}/52",
+				p.elapsed.as_secs_f64(), p.nodes, p.depth, p.seen, p.best_foundation,
+			);
+		});
+	}
+	let result = solver.solve(&game);
+
+	if options.json {
+		print!("{{\"seed\":{},{},", options.seed, json_rules(&options.rules));
+		print!("\"outcome\":\"{}\",\"nodes\":{},", outcome_name(&result.outcome), result.nodes);
+		print!("\"foundation\":{}", result.best.foundation.n_cards_total());
+		if let Outcome::Solved(actions) = &result.outcome {
+			print!(",\"moves\":{}", json_actions(actions));
+		}
+		println!("}}");
+		return;
+	}
+
+	println!("Deal #{}", options.seed);
+	print_board(&game);
+	match result.outcome {
+		Outcome::Solved(actions) => {
+			println!("Solved in {} moves:", actions.len());
+			for (i, &action) in actions.iter().enumerate() {
+				println!("{}: {}", i + 1, action);
+				if options.verbose {
+					game.action(action).unwrap();
+					print_board(&game);
+				}
+			}
+		}
+		outcome => {
+			if outcome == Outcome::Unknown {
+				println!("Gave up.");
+			} else {
+				println!("Unsolvable.");
 			}
-			panic!("Too many steps");
-			return false;
+			println!("Closest position:");
+			game.state = result.best;
+			print_board(&game);
 		}
+	}
+	println!("Explored {} positions.", result.nodes);
+}
 
-		let key = game.state.encode();
-		if self.seen.insert(key) == false {
-			return false;
+fn play(options: &Options) {
+	let deck = Deck::from_seed(options.seed);
+	let mut history = GameHistory::new(Game::with_rules(&deck, options.rules));
+	println!("Deal #{}", options.seed);
+	println!("Enter a move (like n, s-f, s-3, 3-f, fh-3, 1-2), u to undo, r to redo, h for a hint, ? to list moves, or q to quit.");
+	let stdin = io::stdin();
+	let mut lines = stdin.lock().lines();
+	loop {
+		print_board(history.game());
+		if history.game().state.foundation.is_complete() {
+			println!("Won in {} moves!", history.moves().len());
+			return;
+		}
+		print!("> ");
+		io::stdout().flush().unwrap();
+		let line = match lines.next() {
+			Some(line) => line.unwrap(),
+			None => return,
+		};
+		match line.trim() {
+			"q" => return,
+			"u" => if history.undo().is_none() { println!("Nothing to undo."); },
+			"r" => if history.redo().is_none() { println!("Nothing to redo."); },
+			"h" => match history.game().hint(options.max_nodes.unwrap_or(100_000)) {
+				Some(action) => println!("Try {}.", action),
+				None => println!("No moves left."),
+			}
+			"?" => {
+				let mut actions = Vec::new();
+				history.game().for_all_possible_actions(|action| {
+					if history.game().clone().action(action).is_ok() {
+						actions.push(action.to_string());
+					}
+					false
+				});
+				println!("{}", actions.join(" "));
+			}
+			input => match parse_move(history.game(), input) {
+				Some(action) => if let Err(e) = history.action(action) {
+					println!("Can't do that: {}.", e);
+				},
+				None => println!("Unknown move {:?}.", input),
+			}
 		}
+	}
+}
 
+fn assist(options: &Options) {
+	let stdin = io::stdin();
+	let mut lines = stdin.lock().lines();
+	let mut read = |prompt: &str| {
+		print!("{}", prompt);
+		io::stdout().flush().unwrap();
+		lines.next().map(|line| line.unwrap())
+	};
+	let mut assistant = loop {
+		let line = match read("Enter the seven face-up cards from left to right (like ♥Q or hq): ") {
+			Some(line) => line,
+			None => return,
+		};
+		let cards: Result<Vec<Card>, _> = line.split_whitespace().map(str::parse).collect();
+		match cards {
+			Ok(cards) if cards.len() == 7 => {
+				let mut open = [cards[0]; 7];
+				open.copy_from_slice(&cards);
+				match Assistant::new(options.rules, open) {
+					Ok(assistant) => break assistant.agent(Box::new(monte_carlo(options))),
+					Err(e) => println!("Can't do that: {}.", e),
+				}
+			}
+			_ => println!("Expected seven cards."),
+		}
+	};
+	println!("Enter the moves made (like n, s-f, s-3, 3-f, fh-3, 1-2), h for a hint, or q to quit.");
+	loop {
+		if let Some(&i) = assistant.waiting_for().first() {
+			let prompt = if i < 24 { "Card turned over from the stock: " } else { "Card turned face up in the column: " };
+			let line = match read(prompt) {
+				Some(line) => line,
+				None => return,
+			};
+			match line.trim().parse() {
+				Ok(card) => if let Err(e) = assistant.reveal(card) {
+					println!("Can't do that: {}.", e);
+				},
+				Err(_) => println!("Unknown card {:?}.", line.trim()),
+			}
+			continue;
+		}
+		let deck = assistant.view().deck();
+		let game = assistant.view().game(&deck);
+		print_board(&game);
 		if game.state.foundation.is_complete() {
-			for (i, &(e, a)) in self.actions.iter().enumerate() {
-				println!("{}: {:?}", i, a);
-				let game = Game {
-					deck: game.deck,
-					state: GameState::decode(e, &game.deck),
-				};
-				print_board(&game);
+			println!("Won!");
+			return;
+		}
+		let line = match read("> ") {
+			Some(line) => line,
+			None => return,
+		};
+		match line.trim() {
+			"q" => return,
+			"h" => match assistant.hint() {
+				Some(action) => println!("Try {}.", action),
+				None => println!("No moves left."),
+			}
+			input => match parse_move(&game, input) {
+				Some(action) => if let Err(e) = assistant.action(action) {
+					println!("Can't do that: {}.", e);
+				},
+				None => println!("Unknown move {:?}.", input),
 			}
-			return true;
 		}
+	}
+}
 
-		let mut newgame = (*game).clone();
-		game.for_all_possible_actions(|action| {
-			if newgame.action(action).is_ok() {
-				self.actions.push((newgame.state.encode(), action));
-				if self.explore(&newgame, depth + 1) { return true; }
-				self.actions.pop();
-				newgame = (*game).clone();
-			}
-			false
-		})
+/// Parses a move, also accepting `1-2` for moving as many cards as fit.
+fn parse_move(game: &Game, input: &str) -> Option<Action> {
+	if let Ok(action) = input.parse() {
+		return Some(action);
+	}
+	let (from, to) = input.split_at(input.find('-')?);
+	let (from, to): (u8, u8) = (from.parse().ok()?, to[1..].parse().ok()?);
+	let source = game.state.stacks.get(usize::from(from))?;
+	let target = game.state.stacks.get(usize::from(to))?;
+	let n = source.movable_onto(target).unwrap_or(source.len());
+	Some(Action::StackToStack { from, to, n: n as u8 })
+}
+
+fn generate(options: &Options) {
+	for seed in options.seed..options.seed.saturating_add(options.count) {
+		let deck = Deck::from_seed(seed);
+		let cards: Vec<String> = deck.cards.iter().map(|c| c.to_string()).collect();
+		if options.json {
+			let cards: Vec<String> = cards.iter().map(|c| format!("\"{}\"", c)).collect();
+			println!("{{\"seed\":{},\"cards\":[{}]}}", seed, cards.join(","));
+		} else {
+			println!("{}: {}", seed, cards.join(" "));
+		}
+	}
+}
+
+fn stats(options: &Options) {
+	if let Some(agent) = &options.agent {
+		return agent_stats(options, agent);
+	}
+	let rate = win_rate(&options.solver(), options.rules, options.seeds.clone());
+	if let Some(path) = &options.csv {
+		let written = std::fs::File::create(path).and_then(|file| rate.write_csv(io::BufWriter::new(file)));
+		if let Err(e) = written {
+			eprintln!("klondike: can't write {}: {}", path, e);
+			exit(1);
+		}
+	}
+	let (low, high) = rate.interval(1.96);
+	if options.json {
+		println!(
+			"{{{},\"won\":{},\"lost\":{},\"unknown\":{},\"nodes\":{},\"low\":{:.4},\"high\":{:.4}}}",
+			json_rules(&options.rules), rate.won, rate.lost, rate.unknown, rate.nodes(), low, high,
+		);
+	} else {
+		for deal in &rate.deals {
+			println!("{}: {} ({} positions)", deal.seed, outcome_name(&deal.outcome), deal.nodes);
+		}
+		println!("Won: {}, lost: {}, unknown: {}, positions: {}", rate.won, rate.lost, rate.unknown, rate.nodes());
+		println!("Winnable: {:.1}% to {:.1}% (95% confidence)", low * 100.0, high * 100.0);
+	}
+}
+
+fn monte_carlo(options: &Options) -> MonteCarloAgent {
+	let mut solver = options.solver();
+	if options.max_nodes.is_none() {
+		solver = solver.max_nodes(1000);
+	}
+	MonteCarloAgent::new().solver(solver).samples(options.samples).seed(options.seed)
+}
+
+/// Like `stats`, but has an agent play the deals without looking at face-down cards.
+fn agent_stats(options: &Options, name: &str) {
+	let mut agent: Box<dyn Agent> = match name {
+		"greedy" => Box::new(GreedyAgent::new()),
+		_ => Box::new(monte_carlo(options)),
+	};
+	let mut csv = format!("{}\n", CSV_HEADER);
+	let (mut won, mut n) = (0, 0);
+	for seed in options.seeds.clone() {
+		let deck = Deck::from_seed(seed);
+		let result = play_game(&mut *agent, &deck, options.rules, options.max_moves);
+		let outcome = if result.won { "won" } else { "lost" };
+		let foundation = result.state.foundation.n_cards_total();
+		csv += &format!("{},{},,{}\n", seed, outcome, result.moves);
+		if !options.json {
+			println!("{}: {} ({} cards on the foundation)", seed, outcome, foundation);
+		}
+		won += usize::from(result.won);
+		n += 1;
+	}
+	if let Some(path) = &options.csv {
+		if let Err(e) = std::fs::write(path, csv) {
+			eprintln!("klondike: can't write {}: {}", path, e);
+			exit(1);
+		}
+	}
+	let (low, high) = wilson(won, n, 1.96);
+	if options.json {
+		println!(
+			"{{{},\"agent\":\"{}\",\"won\":{},\"lost\":{},\"low\":{:.4},\"high\":{:.4}}}",
+			json_rules(&options.rules), name, won, n - won, low, high,
+		);
+	} else {
+		println!("Won: {}, lost: {}", won, n - won);
+		println!("Win rate: {:.1}% to {:.1}% (95% confidence)", low * 100.0, high * 100.0);
 	}
 }
 
@@ -97,9 +483,9 @@ fn print_board(game: &Game) {
 	}
 	println!();
 	{
-		let mut s = game.state.stock.clone();
-		for _ in 0..3 {
-			match s.top_open_card(&game.deck) {
+		let mut s = game.state.stock;
+		for _ in 0..game.rules.draw {
+			match s.top_open_card(game.deck) {
 				Some(card) => print!(" {:#}", card),
 				None => break,
 			}
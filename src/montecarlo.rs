@@ -0,0 +1,124 @@
+use std::collections::HashSet;
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use crate::*;
+
+/// Picks moves by solving random deals that agree with what has been seen.
+///
+/// For every possible action, a solver with a small budget is run on each
+/// sampled deck. A line that is solved counts twice as much as one that
+/// runs out of budget, and an unsolvable one counts nothing. The action with
+/// the highest total wins, with ties broken by `DefaultEvaluator`.
+///
+/// A safe move (see `Game::safe_move`) is played right away. Like
+/// `GreedyAgent`, it never goes back to a position it has been in before.
+pub struct MonteCarloAgent {
+	solver: Solver,
+	samples: usize,
+	rng: StdRng,
+	visited: HashSet<EncodedGameState>,
+}
+
+impl MonteCarloAgent {
+	/// Uses 10 samples, and a solver that gives up after 1000 positions.
+	pub fn new() -> Self {
+		Self {
+			solver: Solver::new().max_nodes(1000),
+			samples: 10,
+			rng: StdRng::seed_from_u64(0),
+			visited: HashSet::new(),
+		}
+	}
+
+	/// Sets the solver to use on the sampled decks.
+	pub fn solver(mut self, solver: Solver) -> Self {
+		self.solver = solver;
+		self
+	}
+
+	/// Sets the number of decks to sample for every move.
+	pub fn samples(mut self, samples: usize) -> Self {
+		self.samples = samples;
+		self
+	}
+
+	/// Seeds the random number generator used for sampling.
+	pub fn seed(mut self, seed: u64) -> Self {
+		self.rng = StdRng::seed_from_u64(seed);
+		self
+	}
+}
+
+impl Default for MonteCarloAgent {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl Agent for MonteCarloAgent {
+	fn new_game(&mut self, _view: &PlayerView) {
+		self.visited.clear();
+	}
+
+	fn choose(&mut self, view: &PlayerView) -> Option<Action> {
+		self.visited.insert(view.state.encode());
+		let deck = view.deck();
+		let game = view.game(&deck);
+		if let Some(action) = game.safe_move() {
+			return Some(action);
+		}
+		let mut actions = Vec::new();
+		game.for_all_possible_actions(|action| {
+			let mut next = game.clone();
+			if next.action(action).is_ok() && !self.visited.contains(&next.state.encode()) {
+				actions.push(action);
+			}
+			false
+		});
+		if actions.len() < 2 {
+			return actions.first().copied();
+		}
+		let mut scores = vec![0; actions.len()];
+		for _ in 0..self.samples {
+			let deck = view.sample(&mut self.rng);
+			let game = view.game(&deck);
+			for (&action, score) in actions.iter().zip(&mut scores) {
+				let mut next = game.clone();
+				next.action(action).unwrap();
+				*score += match self.solver.solve(&next).outcome {
+					Outcome::Solved(_) => 2,
+					Outcome::Unknown => 1,
+					Outcome::Unsolvable => 0,
+				};
+			}
+		}
+		let evaluator = DefaultEvaluator::new();
+		let mut best: Option<((i32, i32), Action)> = None;
+		for (&action, score) in actions.iter().zip(scores) {
+			let mut next = game.clone();
+			next.action(action).unwrap();
+			let score = (score, evaluator.evaluate(&next));
+			if best.is_none_or(|(s, _)| score > s) {
+				best = Some((score, action));
+			}
+		}
+		best.map(|(_, action)| action)
+	}
+}
+
+#[test]
+fn test() {
+	let agent = || MonteCarloAgent::new().solver(Solver::new().max_nodes(100)).samples(2);
+
+	let result = play_game(&mut agent(), &Deck::new(), Rules::draw_one(), 1000);
+	assert!(result.won);
+
+	// The same seed makes the same choices, so whole games can be replayed.
+	let deck = Deck::from_seed(4);
+	let rules = Rules::draw_three();
+	let first = play_game(&mut agent().seed(7), &deck, rules, 1000);
+	let second = play_game(&mut agent().seed(7), &deck, rules, 1000);
+	assert_eq!(first, second);
+	assert_ne!(first.state, GameState::new(&deck));
+	assert_eq!(first.won, first.state.foundation.is_complete());
+}
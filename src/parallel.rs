@@ -0,0 +1,230 @@
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashSet;
+use std::hash::{Hash, Hasher};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering::Relaxed};
+use std::sync::{Arc, Mutex};
+use crate::*;
+
+const SHARDS: usize = 64;
+
+/// A set of positions that can be shared between threads.
+///
+/// Split into shards with their own lock, to keep threads from waiting on
+/// each other.
+pub(crate) struct TranspositionTable {
+	shards: Vec<Mutex<HashSet<EncodedGameState>>>,
+}
+
+impl TranspositionTable {
+	pub fn new() -> Self {
+		Self {
+			shards: (0..SHARDS).map(|_| Mutex::new(HashSet::new())).collect(),
+		}
+	}
+
+	/// Adds a position, returning false if it was already present.
+	pub fn insert(&self, key: EncodedGameState) -> bool {
+		let mut hasher = DefaultHasher::new();
+		key.hash(&mut hasher);
+		let shard = hasher.finish() as usize % SHARDS;
+		self.shards[shard].lock().unwrap().insert(key)
+	}
+}
+
+/// The state shared by all threads working on the same game.
+pub(crate) struct Shared {
+	pub seen: TranspositionTable,
+	pub stop: AtomicBool,
+	pub nodes: AtomicUsize,
+	/// The number of threads searching right now, and the most there have been.
+	pub searching: AtomicUsize,
+	pub peak: AtomicUsize,
+}
+
+impl Shared {
+	pub fn new() -> Self {
+		Self {
+			seen: TranspositionTable::new(),
+			stop: AtomicBool::new(false),
+			nodes: AtomicUsize::new(0),
+			searching: AtomicUsize::new(0),
+			peak: AtomicUsize::new(0),
+		}
+	}
+}
+
+impl Solver {
+	/// Searches `game` with several threads.
+	///
+	/// The positions near the start are explored breadth-first, and then
+	/// every thread repeatedly takes one of them and searches from there.
+	pub(crate) fn parallel_search(&mut self, game: &Game) -> Outcome {
+		let shared = Arc::new(Shared::new());
+		self.shared = Some(shared.clone());
+		self.visit(&game.state);
+
+		let mut gave_up = false;
+		let mut work = vec![(game.clone(), Vec::new())];
+		while !work.is_empty() && work.len() < self.threads * 8 {
+			let mut next = Vec::new();
+			for (game, path) in work {
+				if path.len() >= self.max_depth {
+					gave_up = true;
+					continue;
+				}
+				let mut actions = Vec::new();
+				game.for_all_search_actions(|action| {
+					actions.push(action);
+					false
+				});
+				for action in actions {
+					let mut newgame = game.clone();
+					if newgame.action(action).is_err() || !self.visit(&newgame.state) {
+						continue;
+					}
+					let mut path = path.clone();
+					path.push(action);
+					if newgame.state.foundation.is_complete() {
+						return Outcome::Solved(path);
+					}
+					next.push((newgame, path));
+				}
+			}
+			work = next;
+		}
+
+		let results = self.run_workers(work, &shared);
+
+		let mut outcome = if gave_up { Outcome::Unknown } else { Outcome::Unsolvable };
+		for (o, nodes, best) in results {
+			self.nodes += nodes;
+			if let Some(best) = best {
+				let n = best.foundation.n_cards_total();
+				if self.best.as_ref().is_none_or(|b| b.foundation.n_cards_total() < n) {
+					self.best = Some(best);
+				}
+			}
+			match o {
+				Outcome::Solved(_) => outcome = o,
+				Outcome::Unknown if outcome == Outcome::Unsolvable => outcome = o,
+				_ => {}
+			}
+		}
+		self.shared = None;
+		outcome
+	}
+
+	/// Lets every thread take positions from `work` and search them, until
+	/// there are none left or one of them is solved.
+	pub(crate) fn run_workers(
+		&self,
+		work: Vec<(Game, Vec<Action>)>,
+		shared: &Arc<Shared>,
+	) -> Vec<(Outcome, usize, Option<GameState>)> {
+		let work = Mutex::new(work);
+		let max_depth = self.max_depth;
+		std::thread::scope(|scope| {
+			let threads: Vec<_> = (0..self.threads).map(|_| {
+				let mut solver = self.fork();
+				solver.shared = Some(shared.clone());
+				let work = &work;
+				scope.spawn(move || {
+					let mut outcome = Outcome::Unsolvable;
+					loop {
+						let item = work.lock().unwrap().pop();
+						let (game, path) = match item {
+							Some(item) => item,
+							None => break,
+						};
+						solver.max_depth = max_depth - path.len();
+						let searching = shared.searching.fetch_add(1, Relaxed) + 1;
+						shared.peak.fetch_max(searching, Relaxed);
+						let result = solver.search(&game);
+						shared.searching.fetch_sub(1, Relaxed);
+						match result {
+							Outcome::Solved(rest) => {
+								shared.stop.store(true, Relaxed);
+								let mut path = path;
+								path.extend(rest);
+								outcome = Outcome::Solved(path);
+								break;
+							}
+							Outcome::Unknown => outcome = Outcome::Unknown,
+							Outcome::Unsolvable => {}
+						}
+					}
+					(outcome, solver.nodes, solver.best)
+				})
+			}).collect();
+			threads.into_iter().map(|t| t.join().unwrap()).collect()
+		})
+	}
+
+	/// Solves many games at once, one per thread at a time.
+	///
+	/// The results are in the same order as the games.
+	pub fn solve_batch(&self, games: &[Game]) -> Vec<SolveResult> {
+		let next = AtomicUsize::new(0);
+		let results = Mutex::new(Vec::with_capacity(games.len()));
+		std::thread::scope(|scope| {
+			for _ in 0..self.threads.min(games.len()) {
+				scope.spawn(|| {
+					let mut solver = self.fork().threads(1);
+					loop {
+						let i = next.fetch_add(1, Relaxed);
+						let game = match games.get(i) {
+							Some(game) => game,
+							None => break,
+						};
+						let result = solver.solve(game);
+						results.lock().unwrap().push((i, result));
+					}
+				});
+			}
+		});
+		let mut results = results.into_inner().unwrap();
+		results.sort_by_key(|&(i, _)| i);
+		results.into_iter().map(|(_, result)| result).collect()
+	}
+}
+
+#[test]
+fn test() {
+	let table = TranspositionTable::new();
+	let deck = Deck::new();
+	let state = GameState::new(&deck);
+	assert!(table.insert(state.encode()));
+	assert!(!table.insert(state.encode()));
+
+	let deck = Deck::from_seed(4);
+	let result = Solver::new().max_nodes(20_000).threads(4).solve(&Game::new(&deck));
+	let actions = result.outcome.unwrap_solved();
+	assert!(verify(&deck, Rules::new(), &actions).unwrap().won);
+
+	// The threads search at the same time, rather than waiting for each other.
+	let deck = Deck::from_seed(1);
+	let game = Game::new(&deck);
+	let mut work = Vec::new();
+	game.for_all_search_actions(|action| {
+		let mut next = game.clone();
+		if next.action(action).is_ok() {
+			work.push((next, vec![action]));
+		}
+		false
+	});
+	let shared = Arc::new(Shared::new());
+	assert!(work.len() > 1);
+	Solver::new().max_nodes(20_000).threads(4).run_workers(work, &shared);
+	assert!(shared.peak.load(Relaxed) > 1);
+
+	let decks: Vec<Deck> = (0..8).map(Deck::from_seed).collect();
+	let games: Vec<Game> = decks.iter().map(Game::new).collect();
+	let solver = Solver::new().max_nodes(1000).threads(3);
+	let results = solver.solve_batch(&games);
+	assert_eq!(results.len(), 8);
+	for (game, result) in games.iter().zip(&results) {
+		let single = Solver::new().max_nodes(1000).solve(game);
+		assert_eq!(result.outcome, single.outcome);
+		assert_eq!(result.nodes, single.nodes);
+	}
+}
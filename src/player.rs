@@ -0,0 +1,379 @@
+use std::collections::HashSet;
+use rand::RngCore;
+use rand::seq::SliceRandom;
+use crate::*;
+
+/// What a player can know about a game: its state, and only the cards that
+/// have been face up at some point.
+#[derive(Clone, Debug)]
+pub struct PlayerView {
+	/// The cards seen so far, by their index in the deck.
+	pub known: [Option<Card>; 52],
+	pub state: GameState,
+	pub rules: Rules,
+}
+
+impl PlayerView {
+	pub fn new(game: &Game) -> Self {
+		let mut view = Self {
+			known: [None; 52],
+			state: game.state.clone(),
+			rules: game.rules,
+		};
+		view.observe(game);
+		view
+	}
+
+	/// Updates the view after a move in the game it was created for.
+	///
+	/// Cards that were seen before stay known, even after they are covered
+	/// or turned face down again. A single action can turn over a card and
+	/// then move it away when the rules move cards to the foundation
+	/// automatically, so use `Game::action_with` to observe every move.
+	pub fn observe(&mut self, game: &Game) {
+		self.state = game.state.clone();
+		self.rules = game.rules;
+		for column in 0..7 {
+			for level in game.state.closed.n_closed(column)..=column {
+				let i = ClosedState::position(column, level);
+				self.known[i] = Some(game.deck.cards[i]);
+			}
+		}
+		for i in 0..24 {
+			if game.state.stock.is_turned(i) {
+				self.known[i] = Some(game.deck.cards[i]);
+			}
+		}
+	}
+
+	/// The cards that have not been seen yet.
+	///
+	/// Besides the cards in `known`, this leaves out the cards that are
+	/// face up in the columns or on the foundation now.
+	pub fn unseen(&self) -> Vec<Card> {
+		let visible = self.visible();
+		Card::all().filter(|&card| !self.known.contains(&Some(card)) && !visible.contains(&card)).collect()
+	}
+
+	/// The cards that are face up in the columns or on the foundation.
+	fn visible(&self) -> Vec<Card> {
+		let stacks = self.state.stacks.iter().flat_map(|stack| (0..stack.len()).map(move |i| stack.get(i).unwrap()));
+		let foundation = Suit::all().flat_map(|suit| {
+			(1..=self.state.foundation.n_cards(suit)).map(move |n| Rank::from_num(n).of(suit))
+		});
+		stacks.chain(foundation).collect()
+	}
+
+	/// Whether the card at `index` in the deck is face down in a column or
+	/// in the stock without having been turned over.
+	fn is_hidden(&self, index: usize) -> bool {
+		if index < 24 {
+			self.state.stock.contains(index) && !self.state.stock.is_turned(index)
+		} else {
+			(0..7).any(|column| {
+				(0..self.state.closed.n_closed(column)).any(|level| ClosedState::position(column, level) == index)
+			})
+		}
+	}
+
+	/// The places in the deck of the cards that have not been seen yet.
+	///
+	/// These are the face-down cards in the columns, and the cards in the
+	/// stock that haven't been turned over.
+	pub fn hidden(&self) -> Vec<usize> {
+		(0..52).filter(|&i| self.known[i].is_none() && self.is_hidden(i)).collect()
+	}
+
+	/// The chance that `card` is at `index` in the deck.
+	///
+	/// All orders of the unseen cards over the hidden places are equally
+	/// likely, so every unseen card is equally likely to be at each hidden
+	/// place. Other places give 1 for the card known to be there, and 0
+	/// otherwise.
+	pub fn probability(&self, index: usize, card: Card) -> f64 {
+		if self.known[index].is_none() && self.is_hidden(index) {
+			let unseen = self.unseen();
+			if unseen.contains(&card) {
+				1.0 / unseen.len() as f64
+			} else {
+				0.0
+			}
+		} else {
+			f64::from(u8::from(self.known[index] == Some(card)))
+		}
+	}
+
+	/// For every hidden place, the chance of every unseen card being there.
+	pub fn probabilities(&self) -> Vec<(usize, Vec<(Card, f64)>)> {
+		let unseen = self.unseen();
+		let p = 1.0 / unseen.len() as f64;
+		self.hidden().into_iter().map(|i| (i, unseen.iter().map(|&card| (card, p)).collect())).collect()
+	}
+
+	/// A deck that agrees with everything seen so far.
+	///
+	/// The unseen cards are put in the remaining places in order.
+	pub fn deck(&self) -> Deck {
+		self.fill(self.unseen())
+	}
+
+	/// A random deck that agrees with everything seen so far.
+	///
+	/// The unseen cards are shuffled into the places of the face-down cards
+	/// and the stock cards that haven't been turned over, so every such deck
+	/// is equally likely.
+	pub fn sample(&self, rng: &mut (impl RngCore + ?Sized)) -> Deck {
+		let mut unseen = self.unseen();
+		unseen.shuffle(rng);
+		self.fill(unseen)
+	}
+
+	/// Puts the unseen cards in the hidden places, in order.
+	///
+	/// Cards that were never seen where they were dealt, because they left
+	/// the stock before the view was made, don't matter for the rest of the
+	/// game. They go in the remaining places.
+	fn fill(&self, unseen: Vec<Card>) -> Deck {
+		let mut unseen = unseen.into_iter();
+		let mut unplaced = self.visible().into_iter().filter(|&card| !self.known.contains(&Some(card)));
+		let mut deck = Deck::new();
+		for (i, (card, known)) in deck.cards.iter_mut().zip(&self.known).enumerate() {
+			*card = match known {
+				Some(known) => *known,
+				None if self.is_hidden(i) => unseen.next().unwrap(),
+				None => unplaced.next().unwrap(),
+			};
+		}
+		deck
+	}
+
+	/// The game played with `deck`, which should agree with `known`, like the one from `deck()`.
+	pub fn game<'a>(&self, deck: &'a Deck) -> Game<'a> {
+		Game {
+			deck,
+			rules: self.rules,
+			state: self.state.clone(),
+		}
+	}
+}
+
+/// Plays a game knowing only what a player can see.
+pub trait Agent {
+	/// Called before the first move of every game.
+	fn new_game(&mut self, _view: &PlayerView) {}
+
+	/// Picks the next action, or `None` to give up.
+	fn choose(&mut self, view: &PlayerView) -> Option<Action>;
+}
+
+/// Picks the action that `DefaultEvaluator` likes best, without going back
+/// to a position it has been in before.
+///
+/// The evaluator only counts cards, so it doesn't need the unseen ones.
+#[derive(Clone, Debug, Default)]
+pub struct GreedyAgent {
+	visited: HashSet<EncodedGameState>,
+}
+
+impl GreedyAgent {
+	pub fn new() -> Self {
+		Self::default()
+	}
+}
+
+impl Agent for GreedyAgent {
+	fn new_game(&mut self, _view: &PlayerView) {
+		self.visited.clear();
+	}
+
+	fn choose(&mut self, view: &PlayerView) -> Option<Action> {
+		self.visited.insert(view.state.encode());
+		let deck = view.deck();
+		let game = view.game(&deck);
+		let evaluator = DefaultEvaluator::new();
+		let mut best: Option<(i32, Action)> = None;
+		game.for_all_possible_actions(|action| {
+			let mut next = game.clone();
+			if next.action(action).is_ok() && !self.visited.contains(&next.state.encode()) {
+				let score = evaluator.evaluate(&next);
+				if best.is_none_or(|(s, _)| score > s) {
+					best = Some((score, action));
+				}
+			}
+			false
+		});
+		best.map(|(_, action)| action)
+	}
+}
+
+/// Plays a game with `agent` until it is won, the agent gives up or makes
+/// an illegal move, or after `max_moves` moves.
+pub fn play_game(agent: &mut (impl Agent + ?Sized), deck: &Deck, rules: Rules, max_moves: usize) -> FinalState {
+	let mut game = Game::with_rules(deck, rules);
+	let mut view = PlayerView::new(&game);
+	agent.new_game(&view);
+	let mut moves = 0;
+	while moves < max_moves && !game.state.foundation.is_complete() {
+		match agent.choose(&view) {
+			Some(action) if game.action_with(action, |game| view.observe(game)).is_ok() => moves += 1,
+			_ => break,
+		}
+	}
+	FinalState {
+		won: game.state.foundation.is_complete(),
+		state: game.state,
+		moves,
+	}
+}
+
+#[test]
+fn test() {
+	let deck = Deck::from_seed(0);
+	let mut game = Game::new(&deck);
+	let mut view = PlayerView::new(&game);
+	assert_eq!(view.known.iter().flatten().count(), 7);
+	assert_eq!(view.unseen().len(), 45);
+	assert_eq!(view.deck().cards[51], deck.cards[51]);
+	game.action(Action::NextStock).unwrap();
+	view.observe(&game);
+	assert_eq!(view.known.iter().flatten().count(), 10);
+	assert_eq!(view.known[23], Some(deck.cards[23]));
+	assert_eq!(view.known[20], None);
+	for (known, &card) in view.known.iter().zip(&deck.cards) {
+		assert!(known.is_none_or(|k| k == card));
+	}
+
+	// Cards that are turned over and moved to the foundation right away are seen too.
+	let mut moved = 0;
+	for seed in 0..20 {
+		let deck = Deck::from_seed(seed);
+		let mut game = Game::with_rules(&deck, Rules { auto_foundation: true, ..Rules::draw_one() });
+		let mut view = PlayerView::new(&game);
+		for _ in 0..24 {
+			game.action_with(Action::NextStock, |game| view.observe(game)).unwrap();
+		}
+		for suit in Suit::all() {
+			for card in (0..game.state.foundation.n_cards(suit)).map(|n| Rank::from_num(n + 1).of(suit)) {
+				assert!(!view.unseen().contains(&card));
+			}
+		}
+		moved += game.state.foundation.n_cards_total();
+	}
+	assert!(moved > 0);
+
+	// The agent can't tell apart decks that only differ in unseen cards.
+	let mut other = deck.clone();
+	other.cards.swap(0, 50);
+	let mut agent = GreedyAgent::new();
+	let a = agent.choose(&PlayerView::new(&Game::new(&deck)));
+	let mut agent = GreedyAgent::new();
+	let b = agent.choose(&PlayerView::new(&Game::new(&other)));
+	assert_eq!(a, b);
+
+	for seed in 0..10 {
+		let deck = Deck::from_seed(seed);
+		let result = play_game(&mut agent, &deck, Rules::draw_one(), 1000);
+		assert_eq!(result.won, result.state.foundation.is_complete());
+	}
+	let result = play_game(&mut agent, &Deck::new(), Rules::draw_three(), 1000);
+	assert!(result.won);
+	assert!(result.moves > 0);
+	let result = play_game(&mut agent, &Deck::new(), Rules::draw_three(), 5);
+	assert!(!result.won);
+	assert_eq!(result.moves, 5);
+
+	// Replaying the same actions on a sampled deck shows the same cards.
+	use rand::SeedableRng;
+	let mut rng = rand::rngs::StdRng::seed_from_u64(1);
+	let deck = Deck::from_seed(3);
+	let mut game = Game::new(&deck);
+	let mut view = PlayerView::new(&game);
+	let mut actions = Vec::new();
+	agent.new_game(&view);
+	while let Some(action) = agent.choose(&view) {
+		game.action(action).unwrap();
+		view.observe(&game);
+		actions.push(action);
+	}
+	let mut different = false;
+	for _ in 0..10 {
+		let sample = view.sample(&mut rng);
+		let mut cards = sample.cards.to_vec();
+		cards.sort();
+		cards.dedup();
+		assert_eq!(cards.len(), 52);
+		let mut replay = Game::new(&sample);
+		let mut replay_view = PlayerView::new(&replay);
+		for &action in &actions {
+			replay.action(action).unwrap();
+			replay_view.observe(&replay);
+		}
+		assert_eq!(replay.state, game.state);
+		assert_eq!(replay_view.known, view.known);
+		different |= sample.cards != deck.cards;
+	}
+	assert!(actions.len() > 10);
+	assert!(different);
+
+	// A view of a game in progress hasn't seen the cards that already left
+	// the stock, but they are face up now.
+	for &rules in &[Rules::draw_one(), Rules { auto_foundation: true, ..Rules::draw_one() }] {
+		let deck = Deck::new();
+		let mut game = Game::with_rules(&deck, rules);
+		let actions = Solver::new().solve(&game).outcome.unwrap_solved();
+		for &action in &actions[..actions.len() / 2] {
+			game.action(action).unwrap();
+		}
+		let view = PlayerView::new(&game);
+		let unseen = view.unseen();
+		for &card in &view.visible() {
+			assert!(!unseen.contains(&card));
+		}
+		let hidden = view.hidden();
+		assert_eq!(hidden.len(), unseen.len());
+		for &i in &hidden {
+			for &card in &view.visible() {
+				assert_eq!(view.probability(i, card), 0.0);
+			}
+			let total: f64 = unseen.iter().map(|&card| view.probability(i, card)).sum();
+			assert!((total - 1.0).abs() < 1e-9);
+		}
+		// The places of the cards that left the stock before the view was made.
+		let gone: Vec<usize> = (0..52).filter(|&i| view.known[i].is_none() && !hidden.contains(&i)).collect();
+		assert!(!gone.is_empty());
+		assert!(gone.iter().all(|&i| i < 24 && !view.state.stock.contains(i)));
+		for _ in 0..10 {
+			let sample = view.sample(&mut rng);
+			let mut cards = sample.cards.to_vec();
+			cards.sort();
+			cards.dedup();
+			assert_eq!(cards.len(), 52);
+			for (i, known) in view.known.iter().enumerate() {
+				assert!(known.is_none_or(|k| k == sample.cards[i]));
+			}
+			for &i in &hidden {
+				assert!(unseen.contains(&sample.cards[i]));
+			}
+		}
+	}
+
+	// Sampling puts the unseen cards at the hidden places as often as expected.
+	let probabilities = view.probabilities();
+	assert_eq!(probabilities.len(), view.hidden().len());
+	assert_eq!(probabilities.len(), view.unseen().len());
+	let (index, cards) = &probabilities[0];
+	for &(card, p) in cards {
+		assert!((p - 1.0 / cards.len() as f64).abs() < 1e-9);
+		assert_eq!(p, view.probability(*index, card));
+	}
+	let total: f64 = cards.iter().map(|&(_, p)| p).sum();
+	assert!((total - 1.0).abs() < 1e-9);
+	let seen = view.known[24].unwrap();
+	assert_eq!(view.probability(24, seen), 1.0);
+	assert_eq!(view.probability(*index, seen), 0.0);
+	let card = cards[0].0;
+	let n = 2000;
+	let hits = (0..n).filter(|_| view.sample(&mut rng).cards[*index] == card).count();
+	let expected = n as f64 / cards.len() as f64;
+	assert!((hits as f64 - expected).abs() < 4.0 * expected.sqrt());
+}
@@ -0,0 +1,99 @@
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct Rules {
+	/// The number of cards turned over from the stock at once.
+	pub draw: u8,
+	/// The number of passes through the stock, or `None` for unlimited.
+	///
+	/// Vegas rules use one pass for draw-one and three for draw-three.
+	/// At most four passes can be tracked.
+	pub passes: Option<u8>,
+	/// Automatically move cards to the foundation when that is always safe.
+	///
+	/// See `Game::safe_move`.
+	pub auto_foundation: bool,
+}
+
+impl Rules {
+	pub const fn new() -> Self {
+		Self { draw: 3, passes: None, auto_foundation: false }
+	}
+
+	pub const fn draw_one() -> Self {
+		Self { draw: 1, passes: None, auto_foundation: false }
+	}
+
+	pub const fn draw_three() -> Self {
+		Self { draw: 3, passes: None, auto_foundation: false }
+	}
+
+	pub const fn vegas_draw_one() -> Self {
+		Self { draw: 1, passes: Some(1), auto_foundation: false }
+	}
+
+	pub const fn vegas_draw_three() -> Self {
+		Self { draw: 3, passes: Some(3), auto_foundation: false }
+	}
+}
+
+impl Default for Rules {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+#[test]
+fn test() {
+	use crate::*;
+
+	let deck = Deck::new();
+
+	let mut game = Game::with_rules(&deck, Rules::draw_one());
+	game.action(Action::NextStock).unwrap();
+	assert_eq!(game.state.stock.top_open_card(&deck), Some(Jack.of(Hearts)));
+	assert_eq!(game.state.stock.n_closed_cards(), 23);
+
+	let mut game = Game::with_rules(&deck, Rules::draw_three());
+	game.action(Action::NextStock).unwrap();
+	assert_eq!(game.state.stock.top_open_card(&deck), Some(Nine.of(Hearts)));
+	assert_eq!(game.state.stock.n_closed_cards(), 21);
+
+	let mut draw_one = Game::with_rules(&deck, Rules::draw_one());
+	let mut draw_three = Game::new(&deck);
+	assert_eq!(draw_three.rules, Rules::draw_three());
+	for _ in 0..3 {
+		draw_one.action(Action::NextStock).unwrap();
+	}
+	draw_three.action(Action::NextStock).unwrap();
+	assert_eq!(draw_one.state, draw_three.state);
+
+	let mut game = Game::with_rules(&deck, Rules::vegas_draw_three());
+	for pass in 0..3 {
+		assert_eq!(game.state.passes, pass);
+		for _ in 0..8 {
+			game.action(Action::NextStock).unwrap();
+		}
+		assert_eq!(game.state.stock.n_closed_cards(), 0);
+		if pass < 2 {
+			game.action(Action::NextStock).unwrap();
+		}
+	}
+	assert_eq!(game.state.passes, 2);
+	assert_eq!(game.action(Action::NextStock), Err(ActionError::NoPassesLeft));
+	assert_eq!(game.state, GameState::decode(game.state.encode(), &deck));
+
+	let mut game = Game::with_rules(&deck, Rules::vegas_draw_one());
+	for _ in 0..24 {
+		game.action(Action::NextStock).unwrap();
+	}
+	assert_eq!(game.action(Action::NextStock), Err(ActionError::NoPassesLeft));
+	game.action(Action::StockToFoundation).unwrap();
+	assert_eq!(game.action(Action::NextStock), Err(ActionError::NoPassesLeft));
+
+	let rules = Rules { auto_foundation: true, ..Rules::draw_one() };
+	let mut game = Game::with_rules(&deck, rules);
+	assert_eq!(game.safe_move(), Some(Action::StackToFoundation(5)));
+	game.action(Action::NextStock).unwrap();
+	assert_eq!(game.state.foundation.n_cards_total(), 1);
+	assert_eq!(game.state.stacks[5], Stack::single(Three.of(Clubs)));
+	assert_eq!(game.safe_move(), None);
+}
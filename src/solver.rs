@@ -0,0 +1,641 @@
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicBool, Ordering::Relaxed};
+use std::time::{Duration, Instant};
+use crate::*;
+use crate::parallel::Shared;
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum Outcome {
+	/// The game is won by playing these actions in order.
+	Solved(Vec<Action>),
+	/// Every reachable position was explored without finding a win.
+	Unsolvable,
+	/// The search gave up before reaching a verdict.
+	Unknown,
+}
+
+#[derive(Clone, Debug)]
+pub struct SolveResult {
+	pub outcome: Outcome,
+	/// Number of positions that were expanded.
+	pub nodes: usize,
+	/// The position with the most cards on the foundation.
+	pub best: GameState,
+}
+
+/// A snapshot of a running search, given to the progress callback.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Progress {
+	/// Number of positions expanded so far.
+	pub nodes: usize,
+	/// Number of actions from the start to the position being explored.
+	pub depth: usize,
+	/// Number of positions remembered.
+	pub seen: usize,
+	/// Most cards on the foundation in any position reached so far.
+	pub best_foundation: usize,
+	/// Time since the search started.
+	pub elapsed: Duration,
+}
+
+type ProgressFn = Arc<Mutex<dyn FnMut(&Progress) + Send>>;
+
+#[derive(Clone)]
+enum Strategy {
+	DepthFirst,
+	BestFirst(Arc<dyn Evaluator + Send + Sync>),
+	Shortest,
+}
+
+/// Stops a running solver from another thread.
+#[derive(Clone, Default, Debug)]
+pub struct CancelToken(Arc<AtomicBool>);
+
+impl CancelToken {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Makes every solver using this token give up as soon as possible.
+	pub fn cancel(&self) {
+		self.0.store(true, Relaxed);
+	}
+
+	pub fn is_cancelled(&self) -> bool {
+		self.0.load(Relaxed)
+	}
+}
+
+pub struct Solver {
+	pub(crate) max_depth: usize,
+	max_nodes: usize,
+	max_seen: usize,
+	time_limit: Option<Duration>,
+	deadline: Option<Instant>,
+	cancel: Option<CancelToken>,
+	progress: Option<(Duration, ProgressFn)>,
+	started: Instant,
+	next_report: Instant,
+	strategy: Strategy,
+	pub(crate) threads: usize,
+	pub(crate) shared: Option<Arc<Shared>>,
+	seen: HashSet<EncodedGameState>,
+	stored: usize,
+	checks: u32,
+	depth: usize,
+	pub(crate) nodes: usize,
+	pub(crate) best: Option<GameState>,
+}
+
+struct Frame<'a> {
+	game: Game<'a>,
+	actions: Vec<Action>,
+	next: usize,
+}
+
+impl<'a> Frame<'a> {
+	fn new(game: Game<'a>) -> Self {
+		let mut actions = Vec::new();
+		game.for_all_search_actions(|action| {
+			actions.push(action);
+			false
+		});
+		Self { game, actions, next: 0 }
+	}
+}
+
+impl Solver {
+	pub fn new() -> Self {
+		Self {
+			max_depth: 500,
+			max_nodes: usize::MAX,
+			max_seen: usize::MAX,
+			time_limit: None,
+			deadline: None,
+			cancel: None,
+			progress: None,
+			started: Instant::now(),
+			next_report: Instant::now(),
+			strategy: Strategy::DepthFirst,
+			threads: 1,
+			shared: None,
+			seen: HashSet::new(),
+			stored: 0,
+			checks: 0,
+			depth: 0,
+			nodes: 0,
+			best: None,
+		}
+	}
+
+	/// Sets the maximum number of actions in a line of play.
+	///
+	/// Positions beyond this depth are not explored, and the outcome will be
+	/// `Unknown` rather than `Unsolvable` if this limit was reached.
+	pub fn max_depth(mut self, max_depth: usize) -> Self {
+		self.max_depth = max_depth;
+		self
+	}
+
+	/// Sets the maximum number of positions to expand before giving up.
+	pub fn max_nodes(mut self, max_nodes: usize) -> Self {
+		self.max_nodes = max_nodes;
+		self
+	}
+
+	/// Sets the maximum number of positions to remember before giving up.
+	///
+	/// This bounds the memory used by the search.
+	pub fn max_seen(mut self, max_seen: usize) -> Self {
+		self.max_seen = max_seen;
+		self
+	}
+
+	/// Sets the maximum time to search for, for each game.
+	pub fn time_limit(mut self, time_limit: Duration) -> Self {
+		self.time_limit = Some(time_limit);
+		self
+	}
+
+	/// Gives up the search when `token` is cancelled.
+	pub fn cancel_token(mut self, token: CancelToken) -> Self {
+		self.cancel = Some(token);
+		self
+	}
+
+	/// Calls `f` about every `interval` while searching.
+	///
+	/// With multiple threads, the calls can come from any of them, with
+	/// the depth and best foundation of that thread.
+	pub fn on_progress(mut self, interval: Duration, f: impl FnMut(&Progress) + Send + 'static) -> Self {
+		self.progress = Some((interval, Arc::new(Mutex::new(f))));
+		self
+	}
+
+	/// Explores the most promising positions first, according to `evaluator`.
+	///
+	/// By default, the solver does a depth-first search.
+	pub fn best_first(mut self, evaluator: impl Evaluator + Send + Sync + 'static) -> Self {
+		self.strategy = Strategy::BestFirst(Arc::new(evaluator));
+		self
+	}
+
+	/// Searches for a solution with as few actions as possible, among the
+	/// actions the search considers.
+	///
+	/// Like the other strategies, it only tries the safe move when there is
+	/// one, and only splits a run of cards when that uncovers a useful card.
+	/// So the result is optimal under that pruning, but a shorter solution
+	/// using the skipped moves may exist.
+	///
+	/// This is an A* search, using the number of cards that are not yet on
+	/// the foundation as a lower bound on the number of remaining actions.
+	/// (Or just one action while some cards remain, if the rules move cards
+	/// to the foundation automatically, since then a single action can move
+	/// many cards.) It keeps track of every position it generates, so it uses
+	/// more memory than the other strategies. It always uses a single thread.
+	pub fn shortest(mut self) -> Self {
+		self.strategy = Strategy::Shortest;
+		self
+	}
+
+	/// Sets the number of threads to search with.
+	///
+	/// The threads share one table of seen positions. By default, the
+	/// solver uses a single thread.
+	pub fn threads(mut self, threads: usize) -> Self {
+		assert!(threads > 0);
+		self.threads = threads;
+		self
+	}
+
+	/// A solver with the same settings and no state.
+	pub(crate) fn fork(&self) -> Self {
+		Self {
+			max_depth: self.max_depth,
+			max_nodes: self.max_nodes,
+			max_seen: self.max_seen,
+			time_limit: self.time_limit,
+			deadline: self.deadline,
+			cancel: self.cancel.clone(),
+			progress: self.progress.clone(),
+			started: self.started,
+			next_report: self.next_report,
+			strategy: self.strategy.clone(),
+			threads: self.threads,
+			..Self::new()
+		}
+	}
+
+	pub fn solve(&mut self, game: &Game) -> SolveResult {
+		self.seen.clear();
+		self.stored = 0;
+		self.checks = 0;
+		self.depth = 0;
+		self.nodes = 0;
+		self.best = None;
+		self.started = Instant::now();
+		self.deadline = self.time_limit.map(|t| self.started + t);
+		if let Some((interval, _)) = &self.progress {
+			self.next_report = self.started + *interval;
+		}
+
+		let outcome = if game.state.foundation.is_complete() {
+			self.visit(&game.state);
+			Outcome::Solved(Vec::new())
+		} else if self.threads > 1 && !matches!(self.strategy, Strategy::Shortest) {
+			self.parallel_search(game)
+		} else {
+			self.search(game)
+		};
+
+		SolveResult {
+			outcome,
+			nodes: self.nodes,
+			best: self.best.take().unwrap_or_else(|| game.state.clone()),
+		}
+	}
+
+	pub(crate) fn search(&mut self, game: &Game) -> Outcome {
+		match self.strategy.clone() {
+			Strategy::DepthFirst => self.depth_first_search(game),
+			Strategy::BestFirst(evaluator) => self.best_first_search(game, &*evaluator),
+			Strategy::Shortest => self.shortest_search(game),
+		}
+	}
+
+	pub(crate) fn visit(&mut self, state: &GameState) -> bool {
+		let key = state.encode_canonical();
+		if let Some(shared) = &self.shared {
+			if !shared.seen.insert(key) {
+				return false;
+			}
+			shared.nodes.fetch_add(1, Relaxed);
+		} else if !self.seen.insert(key) {
+			return false;
+		}
+		self.stored += 1;
+		self.count(state);
+		true
+	}
+
+	/// Counts a position as explored, and remembers it if it is the best so far.
+	fn count(&mut self, state: &GameState) {
+		self.nodes += 1;
+		let n = state.foundation.n_cards_total();
+		if self.best.as_ref().is_none_or(|b| b.foundation.n_cards_total() < n) {
+			self.best = Some(state.clone());
+		}
+	}
+
+	/// Whether the search should stop before visiting another position.
+	pub(crate) fn should_stop(&mut self) -> bool {
+		let (nodes, stored) = match &self.shared {
+			Some(shared) => {
+				if shared.stop.load(Relaxed) {
+					return true;
+				}
+				let n = shared.nodes.load(Relaxed);
+				(n, n)
+			}
+			None => (self.nodes, self.stored),
+		};
+		if nodes >= self.max_nodes || stored >= self.max_seen {
+			return true;
+		}
+		if self.cancel.as_ref().is_some_and(CancelToken::is_cancelled) {
+			return true;
+		}
+		// Looking at the clock is relatively slow, so don't do it every time.
+		let check_clock = self.checks.is_multiple_of(1024);
+		self.checks = self.checks.wrapping_add(1);
+		if !check_clock || (self.deadline.is_none() && self.progress.is_none()) {
+			return false;
+		}
+		let now = Instant::now();
+		if let Some((interval, f)) = &self.progress {
+			if now >= self.next_report {
+				self.next_report = now + *interval;
+				let progress = Progress {
+					nodes,
+					depth: self.depth,
+					seen: stored,
+					best_foundation: self.best.as_ref().map_or(0, |b| b.foundation.n_cards_total()),
+					elapsed: now - self.started,
+				};
+				(f.lock().unwrap())(&progress);
+			}
+		}
+		self.deadline.is_some_and(|deadline| now >= deadline)
+	}
+
+	fn depth_first_search(&mut self, game: &Game) -> Outcome {
+		self.visit(&game.state);
+
+		let mut gave_up = false;
+		let mut path = Vec::new();
+		let mut stack = vec![Frame::new(game.clone())];
+
+		while let Some(frame) = stack.last_mut() {
+			let action = match frame.actions.get(frame.next) {
+				Some(&action) => action,
+				None => {
+					stack.pop();
+					path.pop();
+					self.depth = path.len();
+					continue;
+				}
+			};
+			frame.next += 1;
+
+			let mut newgame = frame.game.clone();
+			if newgame.action(action).is_err() {
+				continue;
+			}
+
+			if path.len() >= self.max_depth {
+				gave_up = true;
+				continue;
+			}
+
+			if self.should_stop() {
+				return Outcome::Unknown;
+			}
+
+			if !self.visit(&newgame.state) {
+				continue;
+			}
+
+			path.push(action);
+			self.depth = path.len();
+
+			if newgame.state.foundation.is_complete() {
+				return Outcome::Solved(path);
+			}
+
+			stack.push(Frame::new(newgame));
+		}
+
+		if gave_up {
+			Outcome::Unknown
+		} else {
+			Outcome::Unsolvable
+		}
+	}
+
+	fn best_first_search(&mut self, game: &Game, evaluator: &dyn Evaluator) -> Outcome {
+		self.visit(&game.state);
+
+		let mut gave_up = false;
+		let mut nodes = vec![Node {
+			game: game.clone(),
+			parent: 0,
+			action: Action::NextStock,
+			depth: 0,
+		}];
+		let mut queue = BinaryHeap::new();
+		queue.push((evaluator.evaluate(game), Reverse(0)));
+
+		'search: loop {
+			let index = match queue.pop() {
+				Some((_, Reverse(index))) => index,
+				None => break if gave_up { Outcome::Unknown } else { Outcome::Unsolvable },
+			};
+			self.depth = nodes[index].depth;
+
+			if nodes[index].depth >= self.max_depth {
+				gave_up = true;
+				continue;
+			}
+
+			let mut actions = Vec::new();
+			nodes[index].game.for_all_search_actions(|action| {
+				actions.push(action);
+				false
+			});
+
+			for action in actions {
+				let mut newgame = nodes[index].game.clone();
+				if newgame.action(action).is_err() {
+					continue;
+				}
+
+				if self.should_stop() {
+					break 'search Outcome::Unknown;
+				}
+
+				if !self.visit(&newgame.state) {
+					continue;
+				}
+
+				let done = newgame.state.foundation.is_complete();
+				let score = evaluator.evaluate(&newgame);
+				let depth = nodes[index].depth + 1;
+				nodes.push(Node { game: newgame, parent: index, action, depth });
+
+				if done {
+					break 'search Outcome::Solved(Node::path(&nodes, nodes.len() - 1));
+				}
+
+				queue.push((score, Reverse(nodes.len() - 1)));
+			}
+		}
+	}
+
+	fn shortest_search(&mut self, game: &Game) -> Outcome {
+		let remaining = |game: &Game| {
+			let n = 52 - game.state.foundation.n_cards_total();
+			if game.rules.auto_foundation { n.min(1) } else { n }
+		};
+
+		let mut gave_up = false;
+		let mut nodes = vec![Node {
+			game: game.clone(),
+			parent: 0,
+			action: Action::NextStock,
+			depth: 0,
+		}];
+		let mut depths = HashMap::new();
+		depths.insert(game.state.encode_canonical(), 0);
+		self.stored += 1;
+		let mut queue = BinaryHeap::new();
+		queue.push((Reverse(remaining(game)), 0, Reverse(0)));
+
+		loop {
+			let (depth, index) = match queue.pop() {
+				Some((_, depth, Reverse(index))) => (depth, index),
+				None => break if gave_up { Outcome::Unknown } else { Outcome::Unsolvable },
+			};
+			self.depth = depth;
+
+			if self.should_stop() {
+				break Outcome::Unknown;
+			}
+
+			// Skip positions that were already reached in fewer actions.
+			if depths[&nodes[index].game.state.encode_canonical()] < depth {
+				continue;
+			}
+			self.count(&nodes[index].game.state);
+
+			if nodes[index].game.state.foundation.is_complete() {
+				break Outcome::Solved(Node::path(&nodes, index));
+			}
+
+			if depth >= self.max_depth {
+				gave_up = true;
+				continue;
+			}
+
+			let mut actions = Vec::new();
+			nodes[index].game.for_all_search_actions(|action| {
+				actions.push(action);
+				false
+			});
+
+			for action in actions {
+				let mut newgame = nodes[index].game.clone();
+				if newgame.action(action).is_err() {
+					continue;
+				}
+				let key = newgame.state.encode_canonical();
+				if depths.get(&key).is_some_and(|&d| d <= depth + 1) {
+					continue;
+				}
+				if depths.insert(key, depth + 1).is_none() {
+					self.stored += 1;
+				}
+				let estimate = depth + 1 + remaining(&newgame);
+				nodes.push(Node { game: newgame, parent: index, action, depth: depth + 1 });
+				queue.push((Reverse(estimate), depth + 1, Reverse(nodes.len() - 1)));
+			}
+		}
+	}
+}
+
+struct Node<'a> {
+	game: Game<'a>,
+	parent: usize,
+	action: Action,
+	depth: usize,
+}
+
+impl Node<'_> {
+	/// The actions that lead from the first node to node `index`.
+	fn path(nodes: &[Node], mut index: usize) -> Vec<Action> {
+		let mut path = Vec::with_capacity(nodes[index].depth);
+		while index != 0 {
+			path.push(nodes[index].action);
+			index = nodes[index].parent;
+		}
+		path.reverse();
+		path
+	}
+}
+
+impl Default for Solver {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+#[cfg(test)]
+impl Outcome {
+	/// The solution, panicking if there is none.
+	pub(crate) fn unwrap_solved(self) -> Vec<Action> {
+		match self {
+			Outcome::Solved(actions) => actions,
+			outcome => panic!("Expected a solution, got {:?}", outcome),
+		}
+	}
+}
+
+#[test]
+fn test() {
+	let deck = Deck::new();
+	for &rules in &[Rules::draw_one(), Rules::draw_three()] {
+		let result = Solver::new().solve(&Game::with_rules(&deck, rules));
+		assert!(result.best.foundation.is_complete());
+		assert!(verify(&deck, rules, &result.outcome.unwrap_solved()).unwrap().won);
+	}
+
+	// Taking a card from the stock isn't forced when drawing three at a time,
+	// since it changes which stock cards can be reached later.
+	let deck_63 = Deck::from_seed(63);
+	let actions = Solver::new().solve(&Game::new(&deck_63)).outcome.unwrap_solved();
+	assert!(verify(&deck_63, Rules::new(), &actions).unwrap().won);
+
+	let result = Solver::new().max_depth(10).solve(&Game::new(&deck));
+	assert_eq!(result.outcome, Outcome::Unknown);
+	assert!(result.nodes > 0);
+
+	for seed in 0..4 {
+		let deck = Deck::from_seed(seed);
+		let result = Solver::new()
+			.max_nodes(20_000)
+			.best_first(DefaultEvaluator::new())
+			.solve(&Game::new(&deck));
+		if let Outcome::Solved(actions) = result.outcome {
+			assert!(verify(&deck, Rules::new(), &actions).unwrap().won);
+		}
+	}
+
+	// Positions are deduplicated up to the order of the columns without
+	// closed cards, but the solutions must use the actual columns.
+	let mut solved = 0;
+	for seed in 0..6 {
+		let deck = Deck::from_seed(seed);
+		let result = Solver::new().max_nodes(20_000).solve(&Game::new(&deck));
+		if let Outcome::Solved(actions) = result.outcome {
+			assert!(verify(&deck, Rules::new(), &actions).unwrap().won);
+			solved += 1;
+		}
+	}
+	assert!(solved > 0);
+
+	let mut game = Game::new(&deck);
+	let mut depth_first = Solver::new().solve(&game).outcome.unwrap_solved();
+	let start: Vec<Action> = depth_first.drain(..30).collect();
+	for &action in &start {
+		game.action(action).unwrap();
+	}
+	let shortest = Solver::new().shortest().solve(&game).outcome.unwrap_solved();
+	assert!(shortest.len() <= depth_first.len());
+	assert!(verify(&deck, Rules::new(), &[&start[..], &shortest[..]].concat()).unwrap().won);
+	let result = Solver::new().shortest().max_depth(shortest.len() - 1).solve(&game);
+	assert_eq!(result.outcome, Outcome::Unknown);
+
+	let game = Game::new(&deck);
+	let result = Solver::new().max_nodes(10).solve(&game);
+	assert_eq!(result.outcome, Outcome::Unknown);
+	assert_eq!(result.nodes, 10);
+
+	let result = Solver::new().shortest().max_seen(100).solve(&game);
+	assert_eq!(result.outcome, Outcome::Unknown);
+	assert!(result.nodes < 100);
+
+	let token = CancelToken::new();
+	let mut solver = Solver::new().cancel_token(token.clone());
+	assert!(matches!(solver.solve(&game).outcome, Outcome::Solved(_)));
+	token.cancel();
+	let result = solver.solve(&game);
+	assert_eq!(result.outcome, Outcome::Unknown);
+	assert_eq!(result.nodes, 1);
+
+	let reports = Arc::new(Mutex::new(Vec::new()));
+	let r = reports.clone();
+	let result = Solver::new()
+		.on_progress(Duration::from_secs(0), move |p| r.lock().unwrap().push(*p))
+		.solve(&game);
+	let reports = reports.lock().unwrap();
+	assert!(!reports.is_empty());
+	assert!(reports.windows(2).all(|w| w[0].nodes <= w[1].nodes));
+	assert!(reports.iter().all(|p| p.nodes <= result.nodes && p.seen == p.nodes));
+	assert!(reports.iter().all(|p| p.depth <= 500 && p.best_foundation <= 52));
+
+	let result = Solver::new().time_limit(Duration::from_secs(0)).solve(&game);
+	assert_eq!(result.outcome, Outcome::Unknown);
+	assert_eq!(result.nodes, 1);
+}
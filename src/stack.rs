@@ -25,6 +25,10 @@ impl Stack {
 		usize::from(self.next & 0xF)
 	}
 
+	pub fn is_empty(&self) -> bool {
+		self.len() == 0
+	}
+
 	pub fn get(&self, i: usize) -> Option<Card> {
 		if i == 0 {
 			self.base
@@ -35,7 +39,7 @@ impl Stack {
 			} else {
 				base.suit().color().opposite()
 			};
-			let variant = Variant::from_bit(self.next >> i + 3 & 1 == 1);
+			let variant = Variant::from_bit((self.next >> (i + 3)) & 1 == 1);
 			let suit = Suit::from_variant(color, variant);
 			let rank = Rank::from_num(base.rank().num() - i);
 			Some(rank.of(suit))
@@ -48,40 +52,62 @@ impl Stack {
 		self.get(self.len().checked_sub(1)?)
 	}
 
-	// TODO: Don't accept cards for an empty stack.
-	pub fn add(&mut self, card: Card) -> Result<(), ()> {
-		if let Some(last) = self.last() {
-			if last.suit().color() != card.suit().color()
-				&& last.rank().num() - 1 == card.rank().num()
-			{
-				if card.suit().variant().as_bit() {
-					self.next |= 1 << self.len() + 3;
-				}
-				self.next += 1;
-				Ok(())
-			} else {
-				Err(())
-			}
-		} else if card.rank() == King {
+	pub fn can_add(&self, card: Card) -> bool {
+		self.check_add(card).is_ok()
+	}
+
+	fn check_add(&self, card: Card) -> Result<(), ActionError> {
+		match self.last() {
+			Some(last) if last.suit().color() == card.suit().color() => Err(ActionError::WrongColor),
+			Some(last) if last.rank().num() != card.rank().num() + 1 => Err(ActionError::WrongRank),
+			Some(_) => Ok(()),
+			None if card.rank() != King => Err(ActionError::NotKing),
+			None => Ok(()),
+		}
+	}
+
+	pub fn add(&mut self, card: Card) -> Result<(), ActionError> {
+		self.check_add(card)?;
+		if self.is_empty() {
 			self.base = Some(card);
 			self.next = 1;
-			Ok(())
 		} else {
-			Err(())
+			if card.suit().variant().as_bit() {
+				self.next |= 1 << (self.len() + 3);
+			}
+			self.next += 1;
 		}
+		Ok(())
 	}
 
-	pub fn take(&mut self) -> Result<Card, ()> {
+	/// The number of cards from the top of this stack that fit onto `target`.
+	///
+	/// There is at most one such number, since the card that goes on top of
+	/// `target` must have a specific rank.
+	pub fn movable_onto(&self, target: &Stack) -> Option<usize> {
+		let base = self.base?;
+		let i = match target.last() {
+			Some(last) => (base.rank().num() + 1).checked_sub(last.rank().num())?,
+			None => 0,
+		};
+		if target.can_add(self.get(i)?) {
+			Some(self.len() - i)
+		} else {
+			None
+		}
+	}
+
+	pub fn take(&mut self) -> Result<Card, ActionError> {
 		if let Some(card) = self.last() {
 			self.next -= 1;
-			if self.len() == 0 {
+			if self.is_empty() {
 				self.base = None;
 			} else {
-				self.next &= !(1 << self.len() + 3)
+				self.next &= !(1 << (self.len() + 3))
 			}
 			Ok(card)
 		} else {
-			Err(())
+			Err(ActionError::EmptySource)
 		}
 	}
 
@@ -102,24 +128,31 @@ impl Stack {
 	}
 }
 
+impl Default for Stack {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
 #[test]
 fn test_single() {
 	let mut stack = Stack::new();
-	assert!(!stack.contains(Nine.of(Hearts)));
+	assert!(!stack.contains(King.of(Hearts)));
 	assert_eq!(stack.len(), 0);
 	assert_eq!(stack.to_bits(0), (0, 0));
 	assert_eq!(Stack::from_bits((0, 0)), (Stack::new(), 0));
-	assert_eq!(stack.take(), Err(()));
-	assert!(stack.add(Nine.of(Hearts)).is_ok());
-	assert!(stack.contains(Nine.of(Hearts)));
-	assert!(!stack.contains(Nine.of(Diamonds)));
-	assert!(!stack.contains(Eight.of(Spades)));
+	assert_eq!(stack.take(), Err(ActionError::EmptySource));
+	assert_eq!(stack.add(Nine.of(Hearts)), Err(ActionError::NotKing));
+	assert!(stack.add(King.of(Hearts)).is_ok());
+	assert!(stack.contains(King.of(Hearts)));
+	assert!(!stack.contains(King.of(Diamonds)));
+	assert!(!stack.contains(Queen.of(Spades)));
 	assert_eq!(stack.len(), 1);
-	assert_eq!(stack, Stack::single(Nine.of(Hearts)));
+	assert_eq!(stack, Stack::single(King.of(Hearts)));
 	assert_eq!(Stack::from_bits(stack.to_bits(2)), (stack.clone(), 2));
-	assert_eq!(stack.take(), Ok(Nine.of(Hearts)));
+	assert_eq!(stack.take(), Ok(King.of(Hearts)));
 	assert_eq!(stack.len(), 0);
-	assert_eq!(stack.take(), Err(()));
+	assert_eq!(stack.take(), Err(ActionError::EmptySource));
 }
 
 #[test]
@@ -133,7 +166,10 @@ fn test_multiple() {
 	assert!(stack.contains(Seven.of(Clubs)));
 	assert!(stack.contains(Six.of(Diamonds)));
 	assert!(!stack.contains(Six.of(Hearts)));
-	assert!(stack.add(Five.of(Diamonds)).is_err());
+	assert!(!stack.can_add(Five.of(Diamonds)));
+	assert!(stack.can_add(Five.of(Clubs)));
+	assert_eq!(stack.add(Five.of(Diamonds)), Err(ActionError::WrongColor));
+	assert_eq!(stack.add(Four.of(Clubs)), Err(ActionError::WrongRank));
 	assert_eq!(stack.len(), 2);
 	assert!(stack.add(Five.of(Clubs)).is_ok());
 	assert_eq!(stack.len(), 3);
@@ -142,6 +178,16 @@ fn test_multiple() {
 	assert!(stack.add(Three.of(Spades)).is_ok());
 	assert_eq!(stack.len(), 5);
 	assert_eq!(Stack::from_bits(stack.to_bits(1)), (stack.clone(), 1));
+	assert_eq!(stack.movable_onto(&Stack::new()), None);
+	assert_eq!(stack.movable_onto(&Stack::single(King.of(Hearts))), None);
+	assert_eq!(stack.movable_onto(&Stack::single(Eight.of(Hearts))), Some(5));
+	assert_eq!(stack.movable_onto(&Stack::single(Eight.of(Spades))), None);
+	assert_eq!(stack.movable_onto(&Stack::single(Six.of(Hearts))), Some(3));
+	assert_eq!(stack.movable_onto(&Stack::single(Six.of(Spades))), None);
+	assert_eq!(stack.movable_onto(&Stack::single(Four.of(Diamonds))), Some(1));
+	assert_eq!(stack.movable_onto(&Stack::single(Four.of(Clubs))), None);
+	assert_eq!(stack.movable_onto(&Stack::single(Three.of(Diamonds))), None);
+	assert_eq!(stack.movable_onto(&Stack::single(Ace.of(Diamonds))), None);
 	assert_eq!(stack.get(0), Some(Seven.of(Clubs)));
 	assert_eq!(stack.get(1), Some(Six.of(Diamonds)));
 	assert_eq!(stack.get(2), Some(Five.of(Clubs)));
@@ -159,6 +205,6 @@ fn test_multiple() {
 	assert_eq!(stack.take(), Ok(Five.of(Clubs)));
 	assert_eq!(stack.take(), Ok(Six.of(Diamonds)));
 	assert_eq!(stack.take(), Ok(Seven.of(Clubs)));
-	assert_eq!(stack.take(), Err(()));
+	assert_eq!(stack.take(), Err(ActionError::EmptySource));
 	assert_eq!(stack, Stack::new());
 }
@@ -1,6 +1,6 @@
 use crate::*;
 
-#[derive(Clone, PartialEq, Eq, Hash)]
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
 pub struct Stock {
 	pub(crate) state: u32,
 }
@@ -31,6 +31,16 @@ impl Stock {
 		}
 	}
 
+	/// Whether the card at `index` in the deck is still in the stock.
+	pub const fn contains(&self, index: usize) -> bool {
+		index < 24 && self.state & 0x8000_0000 >> (23 - index) != 0
+	}
+
+	/// Whether the card at `index` in the deck is still in the stock and has been turned over.
+	pub const fn is_turned(&self, index: usize) -> bool {
+		self.contains(index) && 24 - index <= (self.state & 0xFF) as usize
+	}
+
 	fn open(&mut self) -> Result<(), ()> {
 		match (self.state & !0xFF) << (self.state & 0xFF) {
 			0 => Err(()),
@@ -52,7 +62,7 @@ impl Stock {
 		}
 	}
 
-	pub fn take(&mut self) -> Result<(), ()> {
+	pub fn take(&mut self) -> Result<(), ActionError> {
 		if let Some(n) = (self.state & 0xFF).checked_sub(1) {
 			self.state &= !(0x8000_0000 >> n);
 			let x = self.state >> 8 >> (24 - n);
@@ -63,11 +73,17 @@ impl Stock {
 			}
 			Ok(())
 		} else {
-			Err(())
+			Err(ActionError::EmptySource)
 		}
 	}
 }
 
+impl Default for Stock {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
 #[test]
 fn test() {
 	let deck = Deck::new(); // Sorted deck
@@ -78,7 +94,7 @@ fn test() {
 	//  Closed cards: (bottom) ♠A ♠2 ♠3 ... ♥9 ♥T ♥J (top)
 	//  No open cards.
 
-	assert_eq!(stock.is_empty(), false);
+	assert!(!stock.is_empty());
 	assert_eq!(stock.n_cards(), 24);
 	assert_eq!(stock.n_closed_cards(), 24);
 	assert_eq!(stock.top_open_card(&deck), None);
@@ -87,25 +103,32 @@ fn test() {
 
 	assert_eq!(stock.top_open_card(&deck), Some(Jack.of(Hearts)));
 	assert_eq!(stock.top_open_card(&deck), Some(Jack.of(Hearts)));
+	assert!(stock.is_turned(23));
+	assert!(!stock.is_turned(22));
 
 	stock.next(3);
 
 	assert_eq!(stock.top_open_card(&deck), Some(Eight.of(Hearts)));
 
-	assert_eq!(stock.is_empty(), false);
+	assert!(!stock.is_empty());
 	assert_eq!(stock.n_cards(), 24);
 	assert_eq!(stock.n_closed_cards(), 20);
 
 	assert_eq!(stock.take(), Ok(()));
+	assert!(!stock.is_turned(20));
+	assert!(stock.is_turned(21));
+	assert!(!stock.contains(20));
+	assert!(stock.contains(19));
+	assert!(!stock.is_turned(19));
 
-	assert_eq!(stock.is_empty(), false);
+	assert!(!stock.is_empty());
 	assert_eq!(stock.n_cards(), 23);
 	assert_eq!(stock.n_closed_cards(), 20);
 	assert_eq!(stock.top_open_card(&deck), Some(Nine.of(Hearts)));
 
 	stock.next(1);
 
-	assert_eq!(stock.is_empty(), false);
+	assert!(!stock.is_empty());
 	assert_eq!(stock.n_cards(), 23);
 	assert_eq!(stock.n_closed_cards(), 19);
 	assert_eq!(stock.top_open_card(&deck), Some(Seven.of(Hearts)));
@@ -118,9 +141,9 @@ fn test() {
 	assert_eq!(stock.top_open_card(&deck), Some(Jack.of(Hearts)));
 	assert_eq!(stock.take(), Ok(()));
 	assert_eq!(stock.top_open_card(&deck), None);
-	assert_eq!(stock.take(), Err(()));
+	assert_eq!(stock.take(), Err(ActionError::EmptySource));
 	assert_eq!(stock.top_open_card(&deck), None);
-	assert_eq!(stock.is_empty(), false);
+	assert!(!stock.is_empty());
 	assert_eq!(stock.n_cards(), 19);
 	assert_eq!(stock.n_closed_cards(), 19);
 
@@ -151,19 +174,19 @@ fn test() {
 		assert_eq!(stock.take(), Ok(()));
 	}
 
-	assert_eq!(stock.is_empty(), true);
+	assert!(stock.is_empty());
 	assert_eq!(stock.n_cards(), 0);
 	assert_eq!(stock.n_closed_cards(), 0);
 	assert_eq!(stock.top_open_card(&deck), None);
 
-	assert_eq!(stock.take(), Err(()));
+	assert_eq!(stock.take(), Err(ActionError::EmptySource));
 
 	stock.next(1);
 
-	assert_eq!(stock.is_empty(), true);
+	assert!(stock.is_empty());
 	assert_eq!(stock.n_cards(), 0);
 	assert_eq!(stock.n_closed_cards(), 0);
 	assert_eq!(stock.top_open_card(&deck), None);
 
-	assert_eq!(stock.take(), Err(()));
+	assert_eq!(stock.take(), Err(ActionError::EmptySource));
 }
@@ -0,0 +1,50 @@
+use crate::*;
+
+/// The position after replaying a list of actions.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct FinalState {
+	pub state: GameState,
+	/// Whether all cards ended up on the foundation.
+	pub won: bool,
+	/// The number of actions that were made.
+	pub moves: usize,
+}
+
+/// Replays `actions` from the start of a game with the given deck and rules.
+///
+/// On an illegal action, returns its index together with the error.
+pub fn verify(deck: &Deck, rules: Rules, actions: &[Action]) -> Result<FinalState, (usize, ActionError)> {
+	let mut game = Game::with_rules(deck, rules);
+	for (i, &action) in actions.iter().enumerate() {
+		game.action(action).map_err(|e| (i, e))?;
+	}
+	Ok(FinalState {
+		won: game.state.foundation.is_complete(),
+		state: game.state,
+		moves: actions.len(),
+	})
+}
+
+#[test]
+fn test() {
+	let deck = Deck::new();
+	let rules = Rules::draw_one();
+	let mut actions = Solver::new().solve(&Game::with_rules(&deck, rules)).outcome.unwrap_solved();
+	let result = verify(&deck, rules, &actions).unwrap();
+	assert!(result.won);
+	assert!(result.state.foundation.is_complete());
+	assert_eq!(result.moves, actions.len());
+
+	let last = actions.pop().unwrap();
+	let result = verify(&deck, rules, &actions).unwrap();
+	assert!(!result.won);
+	assert_eq!(result.state.foundation.n_cards_total(), 51);
+
+	actions.push(last);
+	actions.push(last);
+	let n = actions.len();
+	assert_eq!(verify(&deck, rules, &actions), Err((n - 1, ActionError::EmptySource)));
+	actions.insert(1, Action::StackToStack { from: 0, to: 0, n: 1 });
+	assert_eq!(verify(&deck, rules, &actions), Err((1, ActionError::SameColumn)));
+	assert_eq!(verify(&deck, rules, &[]).map(|f| f.won), Ok(false));
+}
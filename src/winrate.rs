@@ -0,0 +1,126 @@
+use std::io;
+use std::ops::Range;
+use crate::*;
+
+/// The columns of the CSV written by `WinRate::write_csv`.
+///
+/// Columns that don't apply are left empty.
+pub const CSV_HEADER: &str = "seed,outcome,nodes,moves";
+
+/// The result of solving a single deal for `win_rate`.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Deal {
+	/// The seed passed to `Deck::from_seed`.
+	pub seed: u64,
+	pub outcome: Outcome,
+	/// The number of positions the solver expanded.
+	pub nodes: usize,
+}
+
+/// The outcomes of solving a range of deals.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct WinRate {
+	pub rules: Rules,
+	pub deals: Vec<Deal>,
+	pub won: usize,
+	pub lost: usize,
+	pub unknown: usize,
+}
+
+/// Solves the deals for all `seeds` with `solver`, using `solve_batch`.
+pub fn win_rate(solver: &Solver, rules: Rules, seeds: Range<u64>) -> WinRate {
+	let decks: Vec<Deck> = seeds.clone().map(Deck::from_seed).collect();
+	let games: Vec<Game> = decks.iter().map(|deck| Game::with_rules(deck, rules)).collect();
+	let results = solver.solve_batch(&games);
+	let mut rate = WinRate { rules, deals: Vec::with_capacity(games.len()), won: 0, lost: 0, unknown: 0 };
+	for (seed, result) in seeds.zip(results) {
+		match result.outcome {
+			Outcome::Solved(_) => rate.won += 1,
+			Outcome::Unsolvable => rate.lost += 1,
+			Outcome::Unknown => rate.unknown += 1,
+		}
+		rate.deals.push(Deal { seed, outcome: result.outcome, nodes: result.nodes });
+	}
+	rate
+}
+
+impl WinRate {
+	/// The total number of positions expanded over all deals.
+	pub fn nodes(&self) -> usize {
+		self.deals.iter().map(|d| d.nodes).sum()
+	}
+
+	/// A confidence interval for the fraction of winnable deals.
+	///
+	/// `z` is the number of standard deviations, e.g. 1.96 for 95%.
+	/// The lower bound counts the unknown deals as lost, and the upper bound
+	/// counts them as won.
+	pub fn interval(&self, z: f64) -> (f64, f64) {
+		let n = self.deals.len();
+		(wilson(self.won, n, z).0, wilson(self.won + self.unknown, n, z).1)
+	}
+
+	/// Writes one line per deal: the seed, the outcome, the number of
+	/// positions, and the length of the solution if there is one.
+	pub fn write_csv(&self, mut w: impl io::Write) -> io::Result<()> {
+		writeln!(w, "{}", CSV_HEADER)?;
+		for deal in &self.deals {
+			let (outcome, moves) = match &deal.outcome {
+				Outcome::Solved(actions) => ("solved", actions.len().to_string()),
+				Outcome::Unsolvable => ("unsolvable", String::new()),
+				Outcome::Unknown => ("unknown", String::new()),
+			};
+			writeln!(w, "{},{},{},{}", deal.seed, outcome, deal.nodes, moves)?;
+		}
+		Ok(())
+	}
+}
+
+/// The Wilson score interval for `k` successes out of `n` trials.
+pub fn wilson(k: usize, n: usize, z: f64) -> (f64, f64) {
+	if n == 0 {
+		return (0.0, 1.0);
+	}
+	let n = n as f64;
+	let p = k as f64 / n;
+	let z2 = z * z;
+	let denominator = 1.0 + z2 / n;
+	let centre = (p + z2 / (2.0 * n)) / denominator;
+	let margin = z * (p * (1.0 - p) / n + z2 / (4.0 * n * n)).sqrt() / denominator;
+	((centre - margin).max(0.0), (centre + margin).min(1.0))
+}
+
+#[test]
+fn test() {
+	let (low, high) = wilson(5, 10, 1.96);
+	assert!((low - 0.2366).abs() < 1e-4);
+	assert!((high - 0.7634).abs() < 1e-4);
+	assert_eq!(wilson(0, 0, 1.96), (0.0, 1.0));
+	assert_eq!(wilson(0, 10, 1.96).0, 0.0);
+	assert_eq!(wilson(10, 10, 1.96).1, 1.0);
+
+	let solver = Solver::new().max_nodes(1000);
+	let rate = win_rate(&solver, Rules::draw_one(), 3..9);
+	assert_eq!(rate.deals.len(), 6);
+	assert_eq!(rate.won + rate.lost + rate.unknown, 6);
+	assert_eq!(rate.deals[0].seed, 3);
+	let deck = Deck::from_seed(3);
+	let single = Solver::new().max_nodes(1000).solve(&Game::with_rules(&deck, Rules::draw_one()));
+	assert_eq!(rate.deals[0].outcome, single.outcome);
+	assert_eq!(rate.deals[0].nodes, single.nodes);
+	assert_eq!(rate.nodes(), rate.deals.iter().map(|d| d.nodes).sum::<usize>());
+	let (low, high) = rate.interval(1.96);
+	assert!(low <= rate.won as f64 / 6.0 && rate.won as f64 / 6.0 <= high);
+
+	let mut csv = Vec::new();
+	rate.write_csv(&mut csv).unwrap();
+	let csv = String::from_utf8(csv).unwrap();
+	let lines: Vec<&str> = csv.lines().collect();
+	assert_eq!(lines.len(), 7);
+	assert_eq!(lines[0], CSV_HEADER);
+	assert!(lines[1].starts_with(&format!("3,{},{},", match single.outcome {
+		Outcome::Solved(_) => "solved",
+		Outcome::Unsolvable => "unsolvable",
+		Outcome::Unknown => "unknown",
+	}, single.nodes)));
+}